@@ -1,6 +1,7 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _parse_assignment_op {
+    // Operator tokens
     (+=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(AddAssign, add_assign, $($t)+););
     (-=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(SubAssign, sub_assign, $($t)+););
     (*=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(MulAssign, mul_assign, $($t)+););
@@ -11,31 +12,56 @@ macro_rules! _parse_assignment_op {
     (^=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(BitXorAssign, bitxor_assign, $($t)+););
     (<<=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(ShlAssign, shl_assign, $($t)+););
     (>>=, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(ShrAssign, shr_assign, $($t)+););
+
+    // Trait names
+    (AddAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(AddAssign, add_assign, $($t)+););
+    (SubAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(SubAssign, sub_assign, $($t)+););
+    (MulAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(MulAssign, mul_assign, $($t)+););
+    (DivAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(DivAssign, div_assign, $($t)+););
+    (RemAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(RemAssign, rem_assign, $($t)+););
+    (BitAndAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(BitAndAssign, bitand_assign, $($t)+););
+    (BitOrAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(BitOrAssign, bitor_assign, $($t)+););
+    (BitXorAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(BitXorAssign, bitxor_assign, $($t)+););
+    (ShlAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(ShlAssign, shl_assign, $($t)+););
+    (ShrAssign, $($t:tt)+) => ($crate::_impl_assignment_op_internal!(ShrAssign, shr_assign, $($t)+););
+
+    // Anything else isn't an operator of this kind
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_assignment_op_internal {
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, &$rhs:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<&$rhs> for $lhs {
+    // `_parse_assignment_op!($op, @if {...} {...})` checks whether `$op` is an assignment operator
+    ($ops_trait:ident, $ops_fn:ident, @if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    // `_parse_assignment_op!($op, @call (lhs, rhs));` applies the operator
+    ($ops_trait:ident, $ops_fn:ident, @call ($($args:tt)*)) => {
+        ::core::ops::$ops_trait::$ops_fn($($args)*);
+    };
+    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait<&$($rhs_lt)? $rhs> for $lhs where $($where_clause)* {
             $(#[$attrs])*
-            fn $ops_fn(&mut self, $rhs_i: &$rhs) {
+            fn $ops_fn(&mut self, $rhs_i: &$($rhs_lt)? $rhs) {
                 #[allow(unused_mut)]
                 let mut $lhs_i = self;
                 $body
             }
         }
 
-        impl$($generic_params)* ::core::ops::$ops_trait<&$rhs> for &mut $lhs {
-            fn $ops_fn(&mut self, $rhs_i: &$rhs) {
-                #[allow(unused_mut)]
-                let mut $lhs_i = self;
-                $body
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait<&$($rhs_lt)? $rhs> for &mut $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn $ops_fn(&mut self, $rhs_i: &$($rhs_lt)? $rhs) {
+                ::core::ops::$ops_trait::$ops_fn(&mut **self, $rhs_i);
             }
         }
     };
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for $lhs {
+    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, own $rhs:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for $lhs where $($where_clause)* {
             $(#[$attrs])*
             fn $ops_fn(&mut self, $rhs_i: $rhs) {
                 #[allow(unused_mut)]
@@ -44,12 +70,20 @@ macro_rules! _impl_assignment_op_internal {
             }
         }
 
-        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for &mut $lhs {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for &mut $lhs where $($where_clause)* {
+            $(#[$attrs])*
             fn $ops_fn(&mut self, $rhs_i: $rhs) {
-                #[allow(unused_mut)]
-                let mut $lhs_i = self;
-                $body
+                ::core::ops::$ops_trait::$ops_fn(&mut **self, $rhs_i);
             }
         }
     };
+    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_assignment_op_internal!(
+            $ops_trait, $ops_fn, $lhs, ref [$($rhs_lt)?] $rhs, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, own $rhs:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_assignment_op_internal!(
+            $ops_trait, $ops_fn, $lhs, own $rhs, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
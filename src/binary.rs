@@ -1,101 +1,201 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _parse_binary_op {
-    (+, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Add, add, $($t)+););
-    (-, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Sub, sub, $($t)+););
-    (*, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Mul, mul, $($t)+););
-    (/, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Div, div, $($t)+););
-    (%, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Rem, rem, $($t)+););
-    (&, $($t:tt)+) => ($crate::_impl_binary_op_internal!(BitAnd, bitand, $($t)+););
-    (|, $($t:tt)+) => ($crate::_impl_binary_op_internal!(BitOr, bitor, $($t)+););
-    (^, $($t:tt)+) => ($crate::_impl_binary_op_internal!(BitXor, bitxor, $($t)+););
-    (<<, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Shl, shl, $($t)+););
-    (>>, $($t:tt)+) => ($crate::_impl_binary_op_internal!(Shr, shr, $($t)+););
+    // Operator tokens
+    (+, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Add], add, Output, $($t)+););
+    (-, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Sub], sub, Output, $($t)+););
+    (*, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Mul], mul, Output, $($t)+););
+    (/, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Div], div, Output, $($t)+););
+    (%, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Rem], rem, Output, $($t)+););
+    (&, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitAnd], bitand, Output, $($t)+););
+    (|, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitOr], bitor, Output, $($t)+););
+    (^, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitXor], bitxor, Output, $($t)+););
+    (<<, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Shl], shl, Output, $($t)+););
+    (>>, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Shr], shr, Output, $($t)+););
+
+    // Trait names
+    (Add, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Add], add, Output, $($t)+););
+    (Sub, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Sub], sub, Output, $($t)+););
+    (Mul, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Mul], mul, Output, $($t)+););
+    (Div, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Div], div, Output, $($t)+););
+    (Rem, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Rem], rem, Output, $($t)+););
+    (BitAnd, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitAnd], bitand, Output, $($t)+););
+    (BitOr, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitOr], bitor, Output, $($t)+););
+    (BitXor, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::BitXor], bitxor, Output, $($t)+););
+    (Shl, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Shl], shl, Output, $($t)+););
+    (Shr, $($t:tt)+) => ($crate::_impl_binary_op_internal!([::core::ops::Shr], shr, Output, $($t)+););
+
+    // Comparison operators
+    (==, $($t:tt)+) => ($crate::_parse_comparison_op!(==, $($t)+););
+    (cmp, $($t:tt)+) => ($crate::_parse_comparison_op!(cmp, $($t)+););
+    (PartialEq, $($t:tt)+) => ($crate::_parse_comparison_op!(PartialEq, $($t)+););
+    (PartialOrd, $($t:tt)+) => ($crate::_parse_comparison_op!(PartialOrd, $($t)+););
+
+    // Index operators, for a borrowed LHS
+    ([], $($t:tt)+) => ($crate::_parse_index_op!([], $($t)+););
+    (Index, $($t:tt)+) => ($crate::_parse_index_op!(Index, $($t)+););
+
+    // User-defined traits, as parsed by `_trait_op_internal`
+    ((trait [$($ops_trait:tt)*] $ops_fn:ident $ops_output:ident), $($t:tt)+) => (
+        $crate::_impl_binary_op_internal!([$($ops_trait)*], $ops_fn, $ops_output, $($t)+);
+    );
+
+    // Anything else isn't an operator of this kind
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _parse_binary_assignment_op {
+    // Calls `$callback!([Trait], fn, ...)` with the assignment operator matching the binary
+    // operator, e.g. `AddAssign` for `+`
+
+    // Operator tokens
+    (+, $callback:path, $($t:tt)+) => ($callback!([::core::ops::AddAssign], add_assign, $($t)+););
+    (-, $callback:path, $($t:tt)+) => ($callback!([::core::ops::SubAssign], sub_assign, $($t)+););
+    (*, $callback:path, $($t:tt)+) => ($callback!([::core::ops::MulAssign], mul_assign, $($t)+););
+    (/, $callback:path, $($t:tt)+) => ($callback!([::core::ops::DivAssign], div_assign, $($t)+););
+    (%, $callback:path, $($t:tt)+) => ($callback!([::core::ops::RemAssign], rem_assign, $($t)+););
+    (&, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitAndAssign], bitand_assign, $($t)+););
+    (|, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitOrAssign], bitor_assign, $($t)+););
+    (^, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitXorAssign], bitxor_assign, $($t)+););
+    (<<, $callback:path, $($t:tt)+) => ($callback!([::core::ops::ShlAssign], shl_assign, $($t)+););
+    (>>, $callback:path, $($t:tt)+) => ($callback!([::core::ops::ShrAssign], shr_assign, $($t)+););
+
+    // Trait names
+    (Add, $callback:path, $($t:tt)+) => ($callback!([::core::ops::AddAssign], add_assign, $($t)+););
+    (Sub, $callback:path, $($t:tt)+) => ($callback!([::core::ops::SubAssign], sub_assign, $($t)+););
+    (Mul, $callback:path, $($t:tt)+) => ($callback!([::core::ops::MulAssign], mul_assign, $($t)+););
+    (Div, $callback:path, $($t:tt)+) => ($callback!([::core::ops::DivAssign], div_assign, $($t)+););
+    (Rem, $callback:path, $($t:tt)+) => ($callback!([::core::ops::RemAssign], rem_assign, $($t)+););
+    (BitAnd, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitAndAssign], bitand_assign, $($t)+););
+    (BitOr, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitOrAssign], bitor_assign, $($t)+););
+    (BitXor, $callback:path, $($t:tt)+) => ($callback!([::core::ops::BitXorAssign], bitxor_assign, $($t)+););
+    (Shl, $callback:path, $($t:tt)+) => ($callback!([::core::ops::ShlAssign], shl_assign, $($t)+););
+    (Shr, $callback:path, $($t:tt)+) => ($callback!([::core::ops::ShrAssign], shr_assign, $($t)+););
+
+    // Anything else has no assignment operator to reuse the LHS with
+    ($op:tt, $callback:path, $($t:tt)+) => ($callback!(@error not_reusable $op););
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_binary_op_internal {
+    // `_parse_binary_op!($op, @if {...} {...})` checks whether `$op` is a binary operator
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, @if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    // `_parse_binary_op!($op, @call out = (lhs, rhs));` binds the result of the operator to `out`, so
+    // other impls can forward to the one running the body. It's a `let` because the `;` ending
+    // `_parse_binary_op!` isn't allowed in an expression
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, @call $out:ident = ($($args:tt)*)) => {
+        let $out = $($ops_trait)*::$ops_fn($($args)*);
+    };
+    // `_parse_binary_op!($op, @call_swapped out = (rhs, lhs));` is the same for the reversed impls of
+    // the commutative macros, which comparison operators need to tell apart
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, @call_swapped $out:ident = ($($args:tt)*)) => {
+        let $out = $($ops_trait)*::$ops_fn($($args)*);
+    };
     // NOTE: In order to prevent a newline in the generated output, it's important the close paren
     // comes *right* after passing `$($generic_params)*` and not on the next line.
-    ($ops_trait:ident, $ops_fn:ident, &$lhs:ty, &$rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, ref [$($lhs_lt:lifetime)?] $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
         $crate::_impl_binary_op_borrowed_borrowed!(
-            $ops_trait, $ops_fn, $lhs, $rhs, $out, $lhs_i, $rhs_i, $(#[$attrs])* $body $($generic_params)*);
+            [$($ops_trait)*], $ops_fn, $ops_output, [$($lhs_lt)?] $lhs, [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body $($generic_params)*);
     };
-    ($ops_trait:ident, $ops_fn:ident, &$lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, ref [$($lhs_lt:lifetime)?] $lhs:ty, own $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
         $crate::_impl_binary_op_borrowed_owned!(
-            $ops_trait, $ops_fn, $lhs, $rhs, $out, $lhs_i, $rhs_i, $(#[$attrs])* $body $($generic_params)*);
+            [$($ops_trait)*], $ops_fn, $ops_output, [$($lhs_lt)?] $lhs, $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body $($generic_params)*);
     };
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, &$rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, own $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
         $crate::_impl_binary_op_owned_borrowed!(
-            $ops_trait, $ops_fn, $lhs, $rhs, $out, $lhs_i, $rhs_i, $(#[$attrs])* $body $($generic_params)*);
+            [$($ops_trait)*], $ops_fn, $ops_output, $lhs, [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body $($generic_params)*);
     };
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, own $lhs:ty, own $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
         $crate::_impl_binary_op_owned_owned!(
-            $ops_trait, $ops_fn, $lhs, $rhs, $out, $lhs_i, $rhs_i, $(#[$attrs])* $body $($generic_params)*);
+            [$($ops_trait)*], $ops_fn, $ops_output, $lhs, $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body $($generic_params)*);
     };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_binary_op_owned_owned {
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for $lhs {
-            type Output = $out;
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<$rhs> for $lhs where $($where_clause)* {
+            type $ops_output = $out;
 
             $(#[$attrs])*
-            fn $ops_fn(self, $rhs_i: $rhs) -> Self::Output {
+            fn $ops_fn(self, $rhs_i: $rhs) -> Self::$ops_output {
                 let $lhs_i = self;
                 $body
             }
         }
     };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_binary_op_owned_owned!(
+            [$($ops_trait)*], $ops_fn, $ops_output, $lhs, $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_binary_op_owned_borrowed {
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<&$rhs> for $lhs {
-            type Output = $out;
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, $lhs:ty, [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<&$($rhs_lt)? $rhs> for $lhs where $($where_clause)* {
+            type $ops_output = $out;
 
             $(#[$attrs])*
-            fn $ops_fn(self, $rhs_i: &$rhs) -> Self::Output {
+            fn $ops_fn(self, $rhs_i: &$($rhs_lt)? $rhs) -> Self::$ops_output {
                 let $lhs_i = self;
                 $body
             }
         }
     };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, $lhs:ty, [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_binary_op_owned_borrowed!(
+            [$($ops_trait)*], $ops_fn, $ops_output, $lhs, [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_binary_op_borrowed_owned {
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<$rhs> for &$lhs {
-            type Output = $out;
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, [$($lhs_lt:lifetime)?] $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<$rhs> for &$($lhs_lt)? $lhs where $($where_clause)* {
+            type $ops_output = $out;
 
             $(#[$attrs])*
-            fn $ops_fn(self, $rhs_i: $rhs) -> Self::Output {
+            fn $ops_fn(self, $rhs_i: $rhs) -> Self::$ops_output {
                 let $lhs_i = self;
                 $body
             }
         }
     };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, [$($lhs_lt:lifetime)?] $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_binary_op_borrowed_owned!(
+            [$($ops_trait)*], $ops_fn, $ops_output, [$($lhs_lt)?] $lhs, $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_binary_op_borrowed_borrowed {
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait<&$rhs> for &$lhs {
-            type Output = $out;
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<&$($rhs_lt)? $rhs> for &$($lhs_lt)? $lhs where $($where_clause)* {
+            type $ops_output = $out;
 
             $(#[$attrs])*
-            fn $ops_fn(self, $rhs_i: &$rhs) -> Self::Output {
+            fn $ops_fn(self, $rhs_i: &$($rhs_lt)? $rhs) -> Self::$ops_output {
                 let $lhs_i = self;
                 $body
             }
         }
     };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ops_output:ident, [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_binary_op_borrowed_borrowed!(
+            [$($ops_trait)*], $ops_fn, $ops_output, [$($lhs_lt)?] $lhs, [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
@@ -0,0 +1,105 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _parse_comparison_op {
+    // Operator tokens
+    (==, $($t:tt)+) => ($crate::_impl_comparison_op_internal!([::core::cmp::PartialEq], eq, bool, [], [], $($t)+););
+    (cmp, $($t:tt)+) => ($crate::_impl_comparison_op_internal!(
+        [::core::cmp::PartialOrd], partial_cmp, ::core::option::Option<::core::cmp::Ordering>,
+        [.map(::core::cmp::Ordering::reverse)], [::core::option::Option::Some(::core::cmp::Ordering::Equal)], $($t)+););
+
+    // Trait names
+    (PartialEq, $($t:tt)+) => ($crate::_parse_comparison_op!(==, $($t)+););
+    (PartialOrd, $($t:tt)+) => ($crate::_parse_comparison_op!(cmp, $($t)+););
+
+    // Anything else isn't an operator of this kind
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
+}
+
+/// Comparison traits take both operands by reference, so the body always gets `&LHS` and `&RHS`
+/// and goes in the impl of `Trait<RHS> for LHS`, which the standard library extends to `&LHS` and
+/// `&RHS`. The other shapes only come from the macros forwarding to that impl, and implement the
+/// trait for `LHS` and `&RHS` or `&LHS` and `RHS` by removing the extra borrow instead of running
+/// the body. `LHS` and `RHS` are already covered by the impl running the body, so they're skipped.
+///
+/// `reverse` is appended to the result when a commutative impl calls the one with the operands the
+/// other way around, and `eq` is what the result of a `PartialOrd` impl is compared against to
+/// implement the `PartialEq` impl it requires. `ret` is the return type of the trait method, but the
+/// methods return the output type as written, so a wrong one is reported instead of ignored.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_comparison_op_internal {
+    (@eq [] $($t:tt)*) => {};
+    (@eq [$($eq:tt)+] $($t:tt)*) => {
+        $crate::_parse_comparison_op!(==, $($t)*);
+    };
+
+    // `_parse_comparison_op!($op, @if {...} {...})` checks whether `$op` is a comparison operator
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], @if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    // `_parse_comparison_op!($op, @output macro, [args...] rest...)` invokes `macro!(args... -> RET rest...)`
+    // with the return type of the trait method, which is the output when the closure leaves it out
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], @output $callback:path, [$($args:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* -> $ret $($tail)*);
+    };
+    // `_parse_comparison_op!($op, @call out = (lhs, rhs));` binds the result of the operator to `out`
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], @call $out:ident = ($($args:tt)*)) => {
+        let $out = $($ops_trait)*::$ops_fn($($args)*);
+    };
+    // `_parse_comparison_op!($op, @call_swapped out = (rhs, lhs));` does the same from an impl with
+    // the operands swapped, so the result is reversed
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], @call_swapped $out:ident = ($($args:tt)*)) => {
+        let $out = $($ops_trait)*::$ops_fn($($args)*)$($reverse)*;
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], ref [$($lhs_lt:lifetime)?] $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<$rhs> for $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn $ops_fn(&self, $rhs_i: &$rhs) -> $out {
+                let $lhs_i = self;
+                $body
+            }
+        }
+
+        $crate::_impl_comparison_op_internal!(@eq [$($eq)*] ref [$($lhs_lt)?] $lhs, ref [$($rhs_lt)?] $rhs, bool, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* {
+            $($ops_trait)*::$ops_fn($lhs_i, $rhs_i) == $($eq)*
+        } [$($where_clause)*] $($generic_params)*);
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], ref [$($lhs_lt:lifetime)?] $lhs:ty, own $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<$rhs> for &$lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn $ops_fn(&self, $rhs_i: &$rhs) -> $out {
+                $($ops_trait)*::$ops_fn(*self, $rhs_i)
+            }
+        }
+
+        $crate::_impl_comparison_op_internal!(
+            @eq [$($eq)*] ref [$($lhs_lt)?] $lhs, own $rhs, bool, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [$($where_clause)*] $($generic_params)*);
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], own $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* $($ops_trait)*<&$rhs> for $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn $ops_fn(&self, $rhs_i: &&$rhs) -> $out {
+                $($ops_trait)*::$ops_fn(self, *$rhs_i)
+            }
+        }
+
+        $crate::_impl_comparison_op_internal!(
+            @eq [$($eq)*] own $lhs, ref [$($rhs_lt)?] $rhs, bool, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [$($where_clause)*] $($generic_params)*);
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], own $lhs:ty, own $rhs:ty, $($tail:tt)*) => {};
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], ref [$($lhs_lt:lifetime)?] $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_comparison_op_internal!(
+            [$($ops_trait)*], $ops_fn, $ret, [$($reverse)*], [$($eq)*], ref [$($lhs_lt)?] $lhs, ref [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], ref [$($lhs_lt:lifetime)?] $lhs:ty, own $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_comparison_op_internal!(
+            [$($ops_trait)*], $ops_fn, $ret, [$($reverse)*], [$($eq)*], ref [$($lhs_lt)?] $lhs, own $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    ([$($ops_trait:tt)*], $ops_fn:ident, $ret:ty, [$($reverse:tt)*], [$($eq:tt)*], own $lhs:ty, ref [$($rhs_lt:lifetime)?] $rhs:ty, $out:ty, $lhs_i:ident, $rhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_comparison_op_internal!(
+            [$($ops_trait)*], $ops_fn, $ret, [$($reverse)*], [$($eq)*], own $lhs, ref [$($rhs_lt)?] $rhs, $out, $lhs_i, $rhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+}
@@ -0,0 +1,58 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _parse_fold_op {
+    // Operator tokens
+    (+, $($t:tt)+) => ($crate::_impl_fold_op_internal!(Sum, sum, Add, add, $($t)+););
+    (*, $($t:tt)+) => ($crate::_impl_fold_op_internal!(Product, product, Mul, mul, $($t)+););
+
+    // Trait names
+    (Add, $($t:tt)+) => ($crate::_impl_fold_op_internal!(Sum, sum, Add, add, $($t)+););
+    (Mul, $($t:tt)+) => ($crate::_impl_fold_op_internal!(Product, product, Mul, mul, $($t)+););
+
+    // Anything else has no iterator trait to go with it
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
+}
+
+/// Implements `Sum` or `Product` for `LHS` over both `LHS` and `&LHS` items, by folding them into
+/// the identity with the operator impls for `LHS` and `RHS` or `&RHS` that `impl_op_ex!` generates
+/// next to these. The impl for borrowed items needs a lifetime of its own, which is put in front of
+/// the generic params of the operator (with their angle brackets split off by
+/// `_split_generic_params_internal`). Its `LHS: 'item` bound is implied on newer compilers, but
+/// not on the oldest supported one.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_fold_op_internal {
+    // `_parse_fold_op!($op, @if {...} {...})` checks whether `$op` has an iterator trait
+    ($iter_trait:ident, $iter_fn:ident, $ops_trait:ident, $ops_fn:ident, @if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    ($iter_trait:ident, $iter_fn:ident, $ops_trait:ident, $ops_fn:ident, $lhs:ty, ($identity:expr), [$(#[$impl_attrs:meta])*] [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::iter::$iter_trait for $lhs where $($where_clause)* {
+            fn $iter_fn<I: ::core::iter::Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($identity, ::core::ops::$ops_trait::$ops_fn)
+            }
+        }
+
+        $crate::_impl_fold_op_internal!(
+            @borrowed [$iter_trait, $iter_fn, $ops_trait, $ops_fn, $lhs, ($identity), [$(#[$impl_attrs])*] [$($where_clause)*]] $($generic_params)*);
+    };
+    (@borrowed $args:tt < $($generic_params:tt)*) => {
+        $crate::_split_generic_params_internal!(@inner $crate::_impl_fold_op_internal, [@borrowed $args] $($generic_params)*);
+    };
+    (@borrowed $args:tt) => {
+        $crate::_impl_fold_op_internal!(@borrowed $args []);
+    };
+    (@borrowed [$iter_trait:ident, $iter_fn:ident, $ops_trait:ident, $ops_fn:ident, $lhs:ty, ($identity:expr), [$(#[$impl_attrs:meta])*] [$($where_clause:tt)*]] [$($generic_params:tt)*]) => {
+        $(#[$impl_attrs])*
+        impl<'item, $($generic_params)*> ::core::iter::$iter_trait<&'item $lhs> for $lhs where $lhs: 'item, $($where_clause)* {
+            fn $iter_fn<I: ::core::iter::Iterator<Item = &'item Self>>(iter: I) -> Self {
+                iter.fold($identity, ::core::ops::$ops_trait::$ops_fn)
+            }
+        }
+    };
+    ($iter_trait:ident, $iter_fn:ident, $ops_trait:ident, $ops_fn:ident, $lhs:ty, ($identity:expr), [$(#[$impl_attrs:meta])*] $($generic_params:tt)*) => {
+        $crate::_impl_fold_op_internal!(
+            $iter_trait, $iter_fn, $ops_trait, $ops_fn, $lhs, ($identity), [$(#[$impl_attrs])*] [] $($generic_params)*);
+    };
+}
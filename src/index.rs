@@ -0,0 +1,94 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _parse_index_op {
+    // Operator tokens
+    ([], $($t:tt)+) => ($crate::_impl_index_op_internal!($($t)+););
+
+    // Trait names
+    (Index, $($t:tt)+) => ($crate::_impl_index_op_internal!($($t)+););
+
+    // Anything else isn't an operator of this kind
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
+}
+
+/// `Index` is implemented for a borrowed LHS and `IndexMut` for a mutably borrowed one. The output
+/// type is what the body returns a borrow of, since `_closure_args_internal` already took off its
+/// `&` or `&mut`. The traits take `self` by reference, so the owned LHS shapes generated by
+/// `impl_op_ex!` are skipped.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_index_op_internal {
+    // `_parse_index_op!($op, @if {...} {...})` checks whether `$op` is an index operator
+    (@if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    // `_parse_index_op!($op, @call out = (lhs, idx));` binds the result of indexing to `out`, and
+    // `@call_mut` does the same for mutable indexing
+    (@call $out:ident = ($($args:tt)*)) => {
+        let $out = ::core::ops::Index::index($($args)*);
+    };
+    (@call_mut $out:ident = ($($args:tt)*)) => {
+        let $out = ::core::ops::IndexMut::index_mut($($args)*);
+    };
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, own $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::Index<$idx> for $lhs where $($where_clause)* {
+            type Output = $out;
+
+            $(#[$attrs])*
+            fn index(&self, $idx_i: $idx) -> &Self::Output {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, ref [$($idx_lt:lifetime)?] $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::Index<&$($idx_lt)? $idx> for $lhs where $($where_clause)* {
+            type Output = $out;
+
+            $(#[$attrs])*
+            fn index(&self, $idx_i: &$($idx_lt)? $idx) -> &Self::Output {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (mutref $lhs:ty, own $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::IndexMut<$idx> for $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn index_mut(&mut self, $idx_i: $idx) -> &mut Self::Output {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (mutref $lhs:ty, ref [$($idx_lt:lifetime)?] $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::IndexMut<&$($idx_lt)? $idx> for $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn index_mut(&mut self, $idx_i: &$($idx_lt)? $idx) -> &mut Self::Output {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (own $lhs:ty, $($tail:tt)*) => {};
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, own $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_index_op_internal!(
+            ref [$($lhs_lt)?] $lhs, own $idx, $out, $lhs_i, $idx_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, ref [$($idx_lt:lifetime)?] $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_index_op_internal!(
+            ref [$($lhs_lt)?] $lhs, ref [$($idx_lt)?] $idx, $out, $lhs_i, $idx_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    (mutref $lhs:ty, own $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_index_op_internal!(
+            mutref $lhs, own $idx, $out, $lhs_i, $idx_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    (mutref $lhs:ty, ref [$($idx_lt:lifetime)?] $idx:ty, $out:ty, $lhs_i:ident, $idx_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_index_op_internal!(
+            mutref $lhs, ref [$($idx_lt)?] $idx, $out, $lhs_i, $idx_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+}
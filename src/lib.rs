@@ -27,8 +27,12 @@
 //! // impl_op!(op |a: LHS, b: &RHS| -> OUT {...});
 //! // impl_op!(op |a: &LHS, b: RHS| -> OUT {...});
 //! // impl_op!(op |a: &LHS, b: &RHS| -> OUT {...});
+//! // impl_op!(op |a: &LHS, b: &RHS| {...});
 //! // where
 //! // OP  : +, -, *, /, %, &, |, ^, <<, >>
+//! //       or the name of the trait (Add, Sub, Mul, Div, Rem, BitAnd, BitOr, BitXor, Shl, Shr)
+//! // OUT : the output type, where `Self` means the owned LHS type (even if `a` is borrowed).
+//! //       Leaving out `-> OUT` is the same as `-> Self`
 //! // a, b: variable names
 //!
 //! use auto_ops::impl_op;
@@ -44,12 +48,14 @@
 //!
 //! impl_op!(- |a: DonkeyKong, b: i32| -> DonkeyKong { DonkeyKong::new(a.bananas - b) });
 //! impl_op!(+ |a: &DonkeyKong, b: &DonkeyKong| -> i32 { a.bananas + b.bananas });
+//! impl_op!(* |a: &DonkeyKong, b: i32| { DonkeyKong::new(a.bananas * b) });
 //!
 //! fn main() {
 //!     let dk = DonkeyKong::new(3) - 1;
 //!     assert_eq!(DonkeyKong::new(2), dk);
 //!     let total_bananas = &dk + &DonkeyKong::new(4);
 //!     assert_eq!(6, total_bananas);
+//!     assert_eq!(DonkeyKong::new(4), &dk * 2);
 //! }
 //! ```
 //! ## Assignment operators
@@ -61,6 +67,7 @@
 //! // where
 //! // attr: any number of attributes
 //! // op  : +=, -=, *=, /=, %=, &=, |=, ^=, <<=, >>=
+//! //       or the name of the trait (AddAssign, SubAssign, ..., ShrAssign)
 //! // a, b: variable names
 //!
 //! // Note: the comma is optional and used solely for better formatting.
@@ -93,9 +100,13 @@
 //! // impl_op!(op #[attr] |a: &LHS| -> OUT {...});
 //! // impl_op!(op, #[attr] |a: LHS| -> OUT {...});
 //! // impl_op!(op, #[attr] |a: &LHS| -> OUT {...});
+//! // impl_op!(op, #[attr] |a: &LHS| {...});
 //! // where
 //! // attr: any number of attributes
-//! // op: !, -
+//! // op: !, - (see below for *)
+//! //     or the name of the trait (Not, Neg)
+//! // OUT: the output type, where `Self` means the owned LHS type (even if `a` is borrowed).
+//! //      Leaving out `-> OUT` is the same as `-> Self`
 //! // a: variable name
 //!
 //! // Note: the comma is optional and used solely for better formatting.
@@ -112,7 +123,7 @@
 //! # }
 //!
 //! impl_op!(- |a: DonkeyKong| -> DonkeyKong { DonkeyKong::new(-a.bananas) });
-//! impl_op!(- |a: &DonkeyKong| -> DonkeyKong { DonkeyKong::new(-a.bananas) });
+//! impl_op!(- |a: &DonkeyKong| -> Self { DonkeyKong::new(-a.bananas) });
 //!
 //! fn main() {
 //!     let dk = -DonkeyKong::new(3);
@@ -120,6 +131,320 @@
 //!     assert_eq!(DonkeyKong::new(3), -&dk);
 //! }
 //! ```
+//! ## Comparison operators
+//! `==` implements `PartialEq` and `cmp` implements `PartialOrd` (along with the `PartialEq` it
+//! requires, which compares the result to `Some(Ordering::Equal)`). Both traits compare their
+//! operands by reference, so the closure takes `&LHS` and `&RHS`. It goes in the impl of
+//! `Trait<RHS> for LHS`, and the standard library extends that to `&LHS` and `&RHS`.
+//! [`impl_op_ex!`](macro.impl_op_ex.html) adds `LHS` with `&RHS` and `&LHS` with `RHS`, and the
+//! commutative macros add the reversed impls, which reverse the `Ordering` returned by `cmp`.
+//! ```
+//! // impl_op!(== |a: &LHS, b: &RHS| -> bool {...});
+//! // impl_op!(cmp |a: &LHS, b: &RHS| -> Option<Ordering> {...});
+//! // where
+//! // op: ==, cmp
+//! //     or the name of the trait (PartialEq, PartialOrd)
+//! // The output type is the return type of the trait method (a different one doesn't compile), and
+//! // can be left out
+//!
+//! use auto_ops::impl_op_ex_commutative;
+//! use std::cmp::Ordering;
+//!
+//! #[derive(Debug)]
+//! struct Meters(f64);
+//! #[derive(Debug)]
+//! struct Feet(f64);
+//!
+//! impl_op_ex_commutative!(cmp |a: &Meters, b: &Feet| -> Option<Ordering> {
+//!     a.0.partial_cmp(&(b.0 * 0.3048))
+//! });
+//!
+//! assert!(Meters(0.3048) == Feet(1.0));
+//! assert!(&Feet(1.0) == Meters(0.3048));
+//! assert!(Meters(1.0) > &Feet(1.0));
+//! assert!(Feet(1.0) < Meters(1.0));
+//! ```
+//! ## Index operators
+//! `[]` implements `Index` from a closure taking `&LHS` and `IndexMut` from one taking `&mut LHS`.
+//! Both return a borrow of the LHS, so they're the only operators whose output type is borrowed
+//! without naming a lifetime. The type it borrows is the trait's `Output`, which `IndexMut` takes
+//! from the `Index` impl for the same index type. [`impl_op_ex!`](macro.impl_op_ex.html) adds the
+//! impls for an owned index when the closure takes it by reference.
+//! ```
+//! // impl_op!([] |a: &LHS, i: IDX| -> &OUT {...});
+//! // impl_op!([] |a: &mut LHS, i: IDX| -> &mut OUT {...});
+//! // where
+//! // IDX: the index type, which can also be borrowed
+//! // The operator can also be given by the name of the trait (Index)
+//!
+//! use auto_ops::{impl_op, impl_op_ex};
+//!
+//! struct Grid {
+//!     width: usize,
+//!     cells: Vec<i32>,
+//! }
+//!
+//! impl_op!([] |a: &Grid, (x, y): (usize, usize)| -> &i32 { &a.cells[y * a.width + x] });
+//! impl_op!([] |a: &mut Grid, (x, y): (usize, usize)| -> &mut i32 { &mut a.cells[y * a.width + x] });
+//! impl_op_ex!([] |a: &Grid, row: &usize| -> &[i32] { &a.cells[row * a.width..][..a.width] });
+//!
+//! let mut grid = Grid { width: 2, cells: vec![0; 4] };
+//! grid[(1, 0)] = 3;
+//! assert_eq!(3, grid[(1, 0)]);
+//! assert_eq!([0, 3], grid[0]);
+//! assert_eq!([0, 0], grid[&1]);
+//! ```
+//! ## Dereferencing
+//! A unary `*` implements `Deref` from a closure taking `&LHS` and `DerefMut` from one taking
+//! `&mut LHS`. Like indexing, both return a borrow of the LHS, and the type it borrows is the
+//! trait's `Target`, which `DerefMut` takes from the `Deref` impl.
+//! ```
+//! // impl_op!(* |a: &LHS| -> &TARGET {...});
+//! // impl_op!(* |a: &mut LHS| -> &mut TARGET {...});
+//! // The operator can also be given by the name of the trait (Deref)
+//!
+//! use auto_ops::impl_op;
+//!
+//! struct Meters(f64);
+//!
+//! impl_op!(* |a: &Meters| -> &f64 { &a.0 });
+//! impl_op!(* |a: &mut Meters| -> &mut f64 { &mut a.0 });
+//!
+//! let mut m = Meters(1.5);
+//! *m += 1.0;
+//! assert_eq!(2.5, *m);
+//! assert_eq!(2.0, m.floor());
+//! ```
+//! ## Attributes
+//! Attributes given after the operator are put on the generated fns, and any that follow `impl` are put
+//! on the generated `impl` blocks instead. Both are applied to every variant the macro generates.
+//! ```
+//! // impl_op!(op #[fn_attr] impl #[impl_attr] |a: LHS, b: RHS| -> OUT {...});
+//! // where
+//! // fn_attr, impl_attr: any number of attributes
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_ex!(+ #[inline] impl #[doc(hidden)] |a: &DonkeyKong, b: &i32| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas + b }
+//! });
+//!
+//! assert_eq!(DonkeyKong { bananas: 3 }, &DonkeyKong { bananas: 1 } + 2);
+//! ```
+//! ## Function syntax and delegation
+//! Instead of a closure, the body can be written like a fn, or the operator can delegate to an
+//! existing function by path. Both forms accept the same types as the closure form (including
+//! leaving out the output type) and generate the same impls.
+//! ```
+//! // impl_op!(op fn(a: LHS, b: RHS) -> OUT {...});
+//! // impl_op!(op LHS, RHS => OUT = path::to::fn);
+//! // impl_op!(op LHS => OUT = path::to::fn);
+//! // impl_op!(op &mut LHS, RHS = path::to::fn);
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl DonkeyKong {
+//!     fn combine(a: &DonkeyKong, b: &DonkeyKong) -> DonkeyKong {
+//!         DonkeyKong { bananas: a.bananas + b.bananas }
+//!     }
+//! }
+//!
+//! impl_op_ex!(+ &DonkeyKong, &DonkeyKong => DonkeyKong = DonkeyKong::combine);
+//! impl_op_ex!(- fn(a: &DonkeyKong) -> DonkeyKong { DonkeyKong { bananas: -a.bananas } });
+//!
+//! let dk = DonkeyKong { bananas: 1 } + &DonkeyKong { bananas: 2 };
+//! assert_eq!(DonkeyKong { bananas: 3 }, dk);
+//! assert_eq!(DonkeyKong { bananas: -3 }, -&dk);
+//! ```
+//! ## Operator lists
+//! A bracketed list of operators generates the full set of impls for each of them. The `$op`
+//! placeholder in the body is replaced with the operator of each impl, so element-wise operators
+//! only need to be written once.
+//! ```
+//! // impl_op!([op1, op2, ...] |a: LHS, b: RHS| -> OUT { ... $op ... });
+//!
+//! use auto_ops::{impl_op, impl_op_ex};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_ex!([+, -, *] |a: &DonkeyKong, b: &DonkeyKong| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas $op b.bananas }
+//! });
+//! impl_op!([+=, -=], |a: &mut DonkeyKong, b: i32| { a.bananas $op b; });
+//!
+//! let mut dk = DonkeyKong { bananas: 3 } * &DonkeyKong { bananas: 2 };
+//! assert_eq!(DonkeyKong { bananas: 6 }, dk);
+//! dk -= 4;
+//! assert_eq!(DonkeyKong { bananas: 2 }, dk);
+//! ```
+//! ## Type lists
+//! In the closure and fn forms, the type of an operand can be a braced list of types, which
+//! generates the impls for each of them (and for every combination, if both operands are lists).
+//! Since the impls are generated separately, this also lets primitives appear on either side of a
+//! commutative operator, where a bare generic couldn't. A borrow can be put in front of the list
+//! or on the types in it, so `b: &{f32, f64}` is the same as `b: {&f32, &f64}`.
+//! ```
+//! // impl_op!(op |a: LHS, b: {RHS1, RHS2, ...}| -> OUT {...});
+//!
+//! use auto_ops::impl_op_ex_commutative;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: f64,
+//! }
+//!
+//! impl_op_ex_commutative!(* |a: &DonkeyKong, b: {f32, f64, u8}| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas * b as f64 }
+//! });
+//!
+//! assert_eq!(DonkeyKong { bananas: 3.0 }, DonkeyKong { bananas: 1.5 } * 2u8);
+//! assert_eq!(DonkeyKong { bananas: 3.0 }, 2f32 * &DonkeyKong { bananas: 1.5 });
+//! ```
+//! ## Type matrices
+//! [`impl_op_matrix!`](macro.impl_op_matrix.html) implements a binary operator for every pair of
+//! two lists of types with one body, taking both operands by reference like
+//! [`impl_op_ex!`](macro.impl_op_ex.html). Pairs that already have impls can be skipped.
+//! ```
+//! // impl_op_matrix!(op [LHS1, LHS2, ...] x [RHS1, RHS2, ...] |a, b| -> OUT {...});
+//! // impl_op_matrix!(op [LHS1, LHS2, ...] x [RHS1, RHS2, ...] skip [(LHS, RHS), ...] |a, b| -> OUT {...});
+//!
+//! use auto_ops::impl_op_matrix;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//! #[derive(Debug, PartialEq)]
+//! struct DiddyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_matrix!(+ [DonkeyKong, DiddyKong] x [DonkeyKong, DiddyKong] skip [(DiddyKong, DiddyKong)] |a, b| -> i32 {
+//!     a.bananas + b.bananas
+//! });
+//!
+//! assert_eq!(3, DonkeyKong { bananas: 1 } + &DiddyKong { bananas: 2 });
+//! assert_eq!(3, &DiddyKong { bananas: 1 } + DonkeyKong { bananas: 2 });
+//! ```
+//! ## Reusing the LHS
+//! With `reuse` after the operator, [`impl_op_ex!`](macro.impl_op_ex.html) implements the variants
+//! with an owned LHS by applying the matching assignment operator to it and returning it, instead
+//! of running the body on a borrow. This saves an allocation per operation for types that own a
+//! buffer. The type needs to implement the assignment operator for the borrowed RHS (or the owned
+//! RHS, if it isn't borrowed), and the output type must be the LHS type.
+//! ```
+//! // impl_op_ex!(op reuse |a: &LHS, b: &RHS| -> LHS {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: Vec<i32>,
+//! }
+//!
+//! impl_op_ex!(+= |a: &mut DonkeyKong, b: &DonkeyKong| {
+//!     a.bananas.extend_from_slice(&b.bananas);
+//! });
+//! impl_op_ex!(+ reuse |a: &DonkeyKong, b: &DonkeyKong| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas.iter().chain(&b.bananas).cloned().collect() }
+//! });
+//!
+//! let dk = DonkeyKong { bananas: vec![1] };
+//! // Runs the body
+//! assert_eq!(DonkeyKong { bananas: vec![1, 1] }, &dk + &dk);
+//! // Pushes onto `dk.bananas`
+//! assert_eq!(DonkeyKong { bananas: vec![1, 2] }, dk + DonkeyKong { bananas: vec![2] });
+//! ```
+//! ## Mutable borrows
+//! With `mut` after the operator, [`impl_op_ex!`](macro.impl_op_ex.html) and
+//! [`impl_op_ex_commutative!`](macro.impl_op_ex_commutative.html) also implement the operator for
+//! `&mut` operands wherever they'd implement it for `&` ones, so code holding a `&mut` doesn't have
+//! to reborrow it. These impls reborrow the operands and call the impls for shared borrows. `mut`
+//! can follow `reuse` as well.
+//! ```
+//! // impl_op_ex!(op mut |a: &LHS, b: &RHS| -> OUT {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_ex!(+ mut |a: &DonkeyKong, b: &DonkeyKong| -> i32 { a.bananas + b.bananas });
+//!
+//! let mut dk = DonkeyKong { bananas: 1 };
+//! let dk = &mut dk;
+//! assert_eq!(2, dk + &DonkeyKong { bananas: 1 });
+//! ```
+//! ## Smart pointers
+//! With the `alloc` feature enabled, `ptr` after the operator makes
+//! [`impl_op_ex!`](macro.impl_op_ex.html) also implement the operator for `Box`, `Rc` and `Arc`
+//! operands wherever it'd implement it for `&` ones. These impls dereference the pointers and call
+//! the impl for shared borrows.
+//!
+//! The orphan rules only allow these impls next to a local type, so a borrowed LHS is assumed to be
+//! local. An impl for `Rc<LHS>` or `Arc<LHS>` would need the RHS to be local as well, so `Rc` and
+//! `Arc` are only generated for the RHS, when both operands are borrowed. The RHS can be a foreign
+//! type such as `&i32`. Unary operators only get `Box`, and operands with a named lifetime don't
+//! get these variants.
+//! ```
+//! // impl_op_ex!(op ptr |a: &LHS, b: &RHS| -> OUT {...});
+//!
+//! # #[cfg(feature = "alloc")] {
+//! use auto_ops::impl_op_ex;
+//! use std::rc::Rc;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_ex!(+ ptr |a: &DonkeyKong, b: &DonkeyKong| -> i32 { a.bananas + b.bananas });
+//!
+//! let dk = Box::new(DonkeyKong { bananas: 1 });
+//! assert_eq!(3, dk + Rc::new(DonkeyKong { bananas: 2 }));
+//! # }
+//! ```
+//! ## Sums and products
+//! With `fold(IDENTITY)` after `+` or `*`, [`impl_op_ex!`](macro.impl_op_ex.html) also implements
+//! `Sum` or `Product` for the LHS type, over both owned and borrowed items. They fold the items
+//! into `IDENTITY` with the operator impls the macro generates, so the closure has to take `&T`
+//! twice and return `T`. The impl attributes, generic params and where clause are copied onto them.
+//! ```
+//! // impl_op_ex!(op fold(IDENTITY) |a: &T, b: &T| -> T {...});
+//! // where
+//! // op: +, * or the name of the trait (Add, Mul)
+//! // IDENTITY: an expression for the `T` that leaves the other operand unchanged
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! struct DonkeyKong {
+//!     pub bananas: i32,
+//! }
+//!
+//! impl_op_ex!(+ fold(DonkeyKong { bananas: 0 }) |a: &DonkeyKong, b: &DonkeyKong| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas + b.bananas }
+//! });
+//!
+//! let kongs = [DonkeyKong { bananas: 1 }, DonkeyKong { bananas: 2 }];
+//! assert_eq!(DonkeyKong { bananas: 3 }, kongs.iter().sum());
+//! assert_eq!(DonkeyKong { bananas: 3 }, kongs.iter().cloned().sum());
+//! ```
 //! ## Generics
 //! Any of the above forms can additionally be generic by putting generic params just before the lambda.
 //! ```
@@ -148,10 +473,247 @@
 //! assert_eq!(Barrel::new(5u8), Barrel::new(2u8) + Barrel::new(3u8));
 //! assert_eq!(Barrel::new(1.2f32), Barrel::new(0.5f32) + Barrel::new(0.7f32));
 //! ```
+//! ## Where clauses
+//! Bounds that can't be written inline in the generic params can go in a `where` clause placed just
+//! before the body (after the return type, if there is one). It is copied onto every generated `impl`.
+//! ```
+//! // impl_op!(op <generic_params> |a: LHS, b: RHS| -> OUT where <predicates> {...});
+//! // impl_op!(op <generic_params> |a: &mut LHS, b: RHS| where <predicates> {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Barrel<T> {
+//!     pub bananas: T,
+//! }
+//!
+//! impl_op_ex!(+ <T> |a: &Barrel<T>, b: &Barrel<T>| -> Barrel<T>
+//!     where for<'a> &'a T: ::core::ops::Add<&'a T, Output = T>
+//! {
+//!     Barrel { bananas: &a.bananas + &b.bananas }
+//! });
+//!
+//! assert_eq!(Barrel { bananas: 5u8 }, Barrel { bananas: 2u8 } + &Barrel { bananas: 3u8 });
+//! ```
+//! ## Lifetimes
+//! Borrowed operands can name a lifetime (declared in the generic params), which lets the output
+//! borrow from them. [`impl_op_ex!`](macro.impl_op_ex.html) doesn't generate the owned variants of
+//! an operand with a named lifetime, since a local borrow couldn't satisfy it.
+//! ```
+//! // impl_op!(op <'a, generic_params> |a: &'a LHS, b: RHS| -> OUT<'a> {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! struct Buffer {
+//!     pub data: Vec<i32>,
+//! }
+//!
+//! impl_op_ex!(% <'a> |a: &'a Buffer, b: &usize| -> &'a [i32] { &a.data[*b..] });
+//!
+//! let buffer = Buffer { data: vec![1, 2, 3] };
+//! assert_eq!(&[2, 3], &buffer % 1);
+//! assert_eq!(&[3], &buffer % &2);
+//! ```
+//! ## Patterns
+//! The closure arguments can use any pattern that is valid in a regular closure, including
+//! destructuring and `ref`/`ref mut` bindings.
+//! ```
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! impl_op_ex!(+ |Point { x: x1, y: y1 }: &Point, Point { x: x2, y: y2 }: &Point| -> Point {
+//!     Point { x: x1 + x2, y: y1 + y2 }
+//! });
+//!
+//! assert_eq!(Point { x: 3, y: 5 }, Point { x: 1, y: 2 } + Point { x: 2, y: 3 });
+//! ```
+//! ## Ownership keywords
+//! Instead of `&T`, `&'a T` and `&mut T`, the type of each argument can be prefixed with `ref`,
+//! `ref 'a` or `mutref` (and owned types with `own`). This is mostly useful when generating calls
+//! to these macros from another macro, since a type captured as `$t:ty` and forwarded as `&$t`
+//! can't always be told apart from an owned type.
+//! ```
+//! // impl_op!(op |a: ref LHS, b: own RHS| -> OUT {...});
+//! // impl_op!(op |a: mutref LHS, b: ref RHS| {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Meters(f32);
+//!
+//! macro_rules! impl_scale {
+//!     ($t:ty) => {
+//!         impl_op_ex!(* |a: ref $t, b: own f32| -> $t { Meters(a.0 * b) });
+//!     };
+//! }
+//!
+//! impl_scale!(Meters);
+//!
+//! assert_eq!(Meters(3.0), Meters(1.5) * 2.0);
+//! assert_eq!(Meters(3.0), &Meters(1.5) * 2.0);
+//! ```
+//! ## Blocks
+//! Several operators on the same type can be implemented with a single
+//! [`impl_ops!`](macro.impl_ops.html) block, which states their attributes, generic params and
+//! where clause once. Each entry is one of the macros above, and `Self` in its signature means the
+//! type after `for`.
+//! ```
+//! // impl_ops! {
+//! //     #[attr] impl #[impl_attr] <generic_params> for Type where <predicates>;
+//! //     impl_op!(op |a: LHS, b: RHS| -> OUT {...});
+//! //     impl_op_ex!(op |a: &Self, b: &Self| -> Self {...});
+//! //     ...
+//! // }
+//!
+//! use auto_ops::impl_ops;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Barrel<T> {
+//!     pub bananas: T,
+//! }
+//!
+//! impl_ops! {
+//!     <T: Copy> for Barrel<T> where T: ::core::ops::Add<Output = T>;
+//!
+//!     impl_op_ex!(+ |a: &Self, b: &Self| -> Self { Barrel { bananas: a.bananas + b.bananas } });
+//!     impl_op_ex!(+ |a: &Self, b: T| -> Self { Barrel { bananas: a.bananas + b } });
+//! }
+//!
+//! assert_eq!(Barrel { bananas: 5 }, Barrel { bananas: 2 } + &Barrel { bananas: 3 });
+//! assert_eq!(Barrel { bananas: 5 }, &Barrel { bananas: 2 } + 3);
+//! ```
+//! ## User-defined traits
+//! Binary operator-like traits defined outside of `core::ops` can be implemented by giving the
+//! path to the trait method instead of an operator. The trait must have the same shape as the
+//! `core::ops` binary traits (a first generic param for the right hand side, an associated output
+//! type, and a method taking `self` and the right hand side). The associated type is assumed to be
+//! called `Output` unless it's named with `type`.
+//! ```
+//! // impl_op_ex!(trait path::to::Trait::method <generic_params> |a: LHS, b: RHS| -> OUT {...});
+//! // impl_op_ex!(trait path::to::Trait::method type Assoc <generic_params> |a: LHS, b: RHS| -> OUT {...});
+//!
+//! use auto_ops::impl_op_ex;
+//!
+//! trait Dot<Rhs = Self> {
+//!     type Output;
+//!     fn dot(self, rhs: Rhs) -> Self::Output;
+//! }
+//!
+//! #[derive(Clone, Copy)]
+//! struct Vector {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! impl_op_ex!(trait Dot::dot |a: &Vector, b: &Vector| -> i32 { a.x * b.x + a.y * b.y });
+//!
+//! let (a, b) = (Vector { x: 1, y: 2 }, Vector { x: 3, y: 4 });
+//! assert_eq!(11, a.dot(b));
+//! assert_eq!(11, (&a).dot(&b));
+//! ```
+//! # Errors
+//! Unsupported forms are rejected with a `compile_error!` naming the macro, the operator and the rule that was broken, e.g.
+//! `` `impl_op!(+= ...)`: assignment operators must take `&mut LHS` as their first argument ``.
+//!
+//! A borrowed output type without a named lifetime:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(- |a: &DonkeyKong| -> &DonkeyKong { a });
+//! ```
+//!
+//! Commutative unary or assignment operators:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op_commutative!(- |a: DonkeyKong| -> DonkeyKong { a });
+//! ```
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op_ex_commutative!(+= |a: &mut DonkeyKong, b: i32| { a.bananas += b; });
+//! ```
+//!
+//! An assignment operator without `&mut`:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(+= |a: DonkeyKong, b: i32| { a.bananas += b; });
+//! ```
+//!
+//! An unsupported operator:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(&& |a: DonkeyKong, b: bool| -> bool { a.bananas > 0 && b });
+//! ```
+//!
+//! A comparison operator with an owned operand:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(== |a: DonkeyKong, b: &i32| -> bool { a.bananas == *b });
+//! ```
+//!
+//! A unary closure for a binary operator:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(/ |a: DonkeyKong| -> DonkeyKong { a });
+//! ```
+//!
+//! Untyped closure arguments:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(+ |a, b| -> i32 { a.bananas + b });
+//! ```
+//!
+//! `reuse` with an operator that has no assignment operator:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op_ex!(- reuse |a: &DonkeyKong| -> DonkeyKong { DonkeyKong { bananas: -a.bananas } });
+//! ```
+//!
+//! An index operator that doesn't return a borrow:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: Vec<i32> }
+//! impl_op!([] |a: &DonkeyKong, i: usize| -> i32 { a.bananas[i] });
+//! ```
+//!
+//! Dereferencing to a borrow of a different mutability:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(* |a: &DonkeyKong| -> &mut i32 { &mut a.bananas });
+//! ```
+//!
+//! `fold` with an operator that has no iterator trait:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op_ex!(- fold(DonkeyKong { bananas: 0 }) |a: &DonkeyKong, b: &DonkeyKong| -> DonkeyKong {
+//!     DonkeyKong { bananas: a.bananas - b.bananas }
+//! });
+//! ```
+//!
+//! No closure at all:
+//! ```compile_fail
+//! # use auto_ops::*;
+//! # struct DonkeyKong { bananas: i32 }
+//! impl_op!(+ DonkeyKong);
+//! ```
 //! # Limitations
-//! * The output type of any operation must be an owned type (i.e. `impl_op!(+ |a: DonkeyKong b: i32| -> &DonkeyKong {...})` is invalid).
-//! * Only some Rust patterns are supported in the closure (`_`, `mut x`, `x`). If you wish to use destructuring or other such patterns, wrap them in parens (`(DonkeyKong { bananas }): DonkeyKong`).
-//! * Cannot put `where` clauses on the `impl`, all generic constraints must be in the generic parameters themselves.
+//! * A borrowed output type must name a lifetime that is declared in the generic params and used by one of the operands (i.e. `impl_op!(+ |a: DonkeyKong, b: i32| -> &DonkeyKong {...})` is invalid), except for index operators and dereferencing.
+//! * `Self` isn't replaced where it starts a path in an [`impl_ops!`](macro.impl_ops.html) entry (i.e. `= Self::add` and `-> Self::Output` are invalid), since a generic type can't be put in front of `::`.
+//! * [`impl_op_ex!`](macro.impl_op_ex.html) can't implement `==` or `cmp` against a bare generic (i.e. `impl_op_ex!(== <T> |a: &SomeType<T>, b: &T| -> bool {...})` is invalid), since the impl for `&SomeType<T> == T` overlaps with the standard library's impl for references. Use `impl_op!` instead.
 //! * Bare generics cannot be used as the type for the first argument in the lambda.
 //!
 //! ```compile_fail
@@ -161,13 +723,34 @@
 //! ```
 mod assignment;
 mod binary;
+mod comparison;
+mod fold;
+mod index;
 mod unary;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// The smart pointers `impl_op_ex!(op ptr ...)` implements operators for
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __alloc {
+    pub use alloc::boxed::Box;
+    pub use alloc::rc::Rc;
+    pub use alloc::sync::Arc;
+}
+
 /// Overloads an operator using the given closure as its body.
 ///
 /// See the [module level documentation](index.html) for more information.
 #[macro_export]
 macro_rules! impl_op {
+    (trait $($args:tt)*) => {
+        $crate::_trait_op_internal!([$crate::_impl_op_internal] [] $($args)*);
+    };
+    ([$($ops:tt),+ $(,)?] $($args:tt)*) => {
+        $crate::_op_list_internal!($crate::impl_op, [] [$($ops),+] $($args)*);
+    };
     // For some reason $(,)? doesn't work here
     ($op:tt , $($args:tt)*) => {
         $crate::_generic_params_shifter_internal!($crate::_impl_op_internal, $op $($args)*);
@@ -180,87 +763,101 @@ macro_rules! impl_op {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_op_internal {
-    // Assignment Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, $rhs_i:tt : &$rhs:ty| $body:block $($generic_params:tt)*) => {
-        $crate::_parse_assignment_op!($op, $lhs, &$rhs, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &mut $lhs, mut $rhs_i: &$rhs| -> () { $body }(lhs, rhs);
-        } $($generic_params)*);
-    };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, mut $rhs_i:ident : $rhs:ty| $body:block $($generic_params:tt)*) => {
-        $crate::_parse_assignment_op!($op, $lhs, $rhs, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &mut $lhs, mut $rhs_i: $rhs| -> () { $body }(lhs, rhs);
-        } $($generic_params)*);
-    };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, $rhs_i:tt : $rhs:ty| $body:block $($generic_params:tt)*) => {
-        $crate::_parse_assignment_op!($op, $lhs, $rhs, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &mut $lhs, $rhs_i: $rhs| -> () { $body }(lhs, rhs);
-        } $($generic_params)*);
-    };
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op, $($tail)*);
+    );
 
-    // Unary Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_unary_op!($op, &$lhs, $out, lhs, $(#[$attrs])* {
-            |$lhs_i: &$lhs| -> $out { $body }(lhs)
-        } $($generic_params)*);
-    };
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_unary_op!($op, $lhs, $out, lhs, $(#[$attrs])* {
-            |mut $lhs_i: $lhs| -> $out { $body }(lhs)
+    // Assignment Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| $body:block $($generic_params:tt)*) => {
+        $crate::_parse_assignment_op!($op, $lhs, ref [$($rhs_lt)?] $rhs, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            // The impl for `&mut LHS` passes a `&mut &mut LHS`, which isn't coerced when the pattern
+            // has `ref` bindings
+            let lhs: &mut $lhs = lhs;
+            let $($lhs_i)* = lhs;
+            let $($rhs_i)*: &$($rhs_lt)? $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_unary_op!($op, $lhs, $out, lhs, $(#[$attrs])* {
-            |$lhs_i: $lhs| -> $out { $body }(lhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| $body:block $($generic_params:tt)*) => {
+        $crate::_parse_assignment_op!($op, $lhs, own $rhs, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            // The impl for `&mut LHS` passes a `&mut &mut LHS`, which isn't coerced when the pattern
+            // has `ref` bindings
+            let lhs: &mut $lhs = lhs;
+            let $($lhs_i)* = lhs;
+            let $($rhs_i)*: $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
 
-    // Binary Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, &$lhs, &$rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &$lhs, $rhs_i: &$rhs| -> $out { $body }(lhs, rhs)
+    // Mutable Index Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_index_op!($op, mutref $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &mut $lhs = lhs;
+            let $($rhs_i)*: &$($rhs_lt)? $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, &$lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &$lhs, mut $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_index_op!($op, mutref $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &mut $lhs = lhs;
+            let $($rhs_i)*: $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, &$lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: &$lhs, $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    // Unary Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_unary_op!($op, mutref $lhs, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &mut $lhs = lhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, &$rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |mut $lhs_i: $lhs, $rhs_i: &$rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_unary_op!($op, ref [$($lhs_lt)?] $lhs, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &$($lhs_lt)? $lhs = lhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, &$rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: $lhs, $rhs_i: &$rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_unary_op!($op, own $lhs, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: $lhs = lhs;
+            $body
         } $($generic_params)*);
     };
 
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |mut $lhs_i: $lhs, mut $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    // Binary Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_binary_op!($op, ref [$($lhs_lt)?] $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &$($lhs_lt)? $lhs = lhs;
+            let $($rhs_i)*: &$($rhs_lt)? $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |mut $lhs_i: $lhs, $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_binary_op!($op, ref [$($lhs_lt)?] $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: &$($lhs_lt)? $lhs = lhs;
+            let $($rhs_i)*: $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: $lhs, mut $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_binary_op!($op, own $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: $lhs = lhs;
+            let $($rhs_i)*: &$($rhs_lt)? $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {
-            |$lhs_i: $lhs, $rhs_i: $rhs| -> $out { $body }(lhs, rhs)
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => {
+        $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+            let $($lhs_i)*: $lhs = lhs;
+            let $($rhs_i)*: $rhs = rhs;
+            $body
         } $($generic_params)*);
     };
+
+    // Where clauses can't be matched in place, so split them off and try again
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_internal, [$op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
+    );
 }
 
 /// Overloads an operator using the given closure as its body. Generates overloads for both owned and borrowed variants where possible.
@@ -286,6 +883,17 @@ macro_rules! _impl_op_internal {
 /// impl_op!(op |a: LHS, b: RHS| -> OUT {...});
 /// impl_op!(op |a: &LHS, b: RHS| -> OUT {...});
 /// ```
+///
+/// The body only ends up in the impl with the types as written. The other impls call that one
+/// instead of repeating the body, so a large body is only compiled once.
+///
+/// With `reuse` after the operator, the variants with an owned LHS apply the matching assignment
+/// operator to it instead (see [Reusing the LHS](index.html#reusing-the-lhs)).
+/// With `mut`, the borrowed operands also get `&mut` variants (see
+/// [Mutable borrows](index.html#mutable-borrows)). With `ptr`, they also get `Box` variants, and a
+/// borrowed RHS gets `Rc` and `Arc` ones, which needs the `alloc` feature (see [Smart pointers](index.html#smart-pointers)).
+/// With `fold(IDENTITY)` after `+` or `*`, the LHS type also gets `Sum` or `Product` impls (see
+/// [Sums and products](index.html#sums-and-products)).
 /// # Examples
 /// ```
 /// use auto_ops::impl_op_ex;
@@ -313,6 +921,27 @@ macro_rules! _impl_op_internal {
 /// }
 #[macro_export]
 macro_rules! impl_op_ex {
+    (trait $($args:tt)*) => {
+        $crate::_trait_op_internal!([$crate::_impl_op_ex_internal] [] $($args)*);
+    };
+    ([$($ops:tt),+ $(,)?] $($args:tt)*) => {
+        $crate::_op_list_internal!($crate::impl_op_ex, [] [$($ops),+] $($args)*);
+    };
+    ($op:tt $(,)? reuse mut $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_reuse_mut_internal, $op $($args)*);
+    };
+    ($op:tt $(,)? reuse $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_reuse_internal, $op $($args)*);
+    };
+    ($op:tt $(,)? mut $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_mut_internal, $op $($args)*);
+    };
+    ($op:tt $(,)? ptr $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_ptr_internal, $op $($args)*);
+    };
+    ($op:tt $(,)? fold($identity:expr) $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_fold_internal, [@fold ($identity)] $op $($args)*);
+    };
     // For some reason $(,)? doesn't work here
     ($op:tt , $($args:tt)*) => {
         $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_internal, $op $($args)*);
@@ -325,65 +954,365 @@ macro_rules! impl_op_ex {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_op_ex_internal {
-    // Assignment Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, $rhs_i:tt : &$rhs:ty| $body:block $($generic_params:tt)*) => (
-        $crate::_parse_assignment_op!($op, $lhs, &$rhs, lhs, rhs, $(#[$attrs])* {|$lhs_i : &mut $lhs, $rhs_i : &$rhs| -> () {$body} (lhs, rhs);} $($generic_params)*);
-        $crate::_parse_assignment_op!($op, $lhs, $rhs, lhs, rhs, $(#[$attrs])* {|$lhs_i : &mut $lhs, $rhs_i : &$rhs| -> () {$body} (lhs, &rhs);} $($generic_params)*);
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, mut $rhs_i:ident : $rhs:ty| $body:block $($generic_params:tt)*) => (
-        $crate::_parse_assignment_op!($op, $lhs, $rhs, lhs, rhs, $(#[$attrs])* {|$lhs_i : &mut $lhs, mut $rhs_i : $rhs| -> () {$body} (lhs, rhs);} $($generic_params)*);
+
+    // Assignment Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_assignment_op!($op, $lhs, own $rhs, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_assignment_op!($op, @call (lhs, &rhs)); } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $lhs, [$($rhs_i)*]: own $rhs| $body $($generic_params)*);
+    );
+
+    // Mutable Index Ops
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_index_op!($op, mutref $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_index_op!($op, @call_mut out = (lhs, &rhs)); out } $($generic_params)*);
+        );
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &mut $lhs:ty, $rhs_i:tt : $rhs:ty| $body:block $($generic_params:tt)*) => (
-        $crate::_parse_assignment_op!($op, $lhs, $rhs, lhs, rhs, $(#[$attrs])* {|$lhs_i : &mut $lhs, $rhs_i : $rhs| -> () {$body} (lhs, rhs);} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
     );
 
     // Unary Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::_parse_unary_op!($op, &$lhs, $out, lhs, $(#[$attrs])* {|$lhs_i : &$lhs| -> $out {$body} (lhs)} $($generic_params)*);
-        $crate::_parse_unary_op!($op, $lhs, $out, lhs, $(#[$attrs])* {|$lhs_i : &$lhs| -> $out {$body} (&lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $lhs| -> $out $body $($generic_params)*);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty|  -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::_parse_unary_op!($op, $lhs, $out, lhs, $(#[$attrs])* {|mut $lhs_i : $lhs| -> $out {$body} (lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_unary_op!($op, own $lhs, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_unary_op!($op, @call out = (&lhs)); out } $($generic_params)*);
+        );
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty|  -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::_parse_unary_op!($op, $lhs, $out, lhs, $(#[$attrs])* {|$lhs_i : $lhs| -> $out {$body} (lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs| -> $out $body $($generic_params)*);
     );
 
     // Binary Ops
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, &$lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (lhs, &rhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, &$rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (&lhs, rhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (&lhs, &rhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, ref [$($lhs_lt)?] $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&lhs, rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&lhs, &rhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&lhs, rhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &rhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+    );
+
+    // Where clauses can't be matched in place, so split them off and try again
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_internal, [$op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_reuse_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
+    );
+
+    // The borrowed LHS variants run the body, the owned ones apply the assignment operator in place
+    ([$($assign_trait:tt)*], $assign_fn:ident, $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, ref [$($lhs_lt)?] $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+                let mut lhs = lhs;
+                $($assign_trait)*::$assign_fn(&mut lhs, rhs);
+                lhs
+            } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+                let mut lhs = lhs;
+                $($assign_trait)*::$assign_fn(&mut lhs, &rhs);
+                lhs
+            } $($generic_params)*);
+        );
+    );
+    ([$($assign_trait:tt)*], $assign_fn:ident, $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* {
+                let mut lhs = lhs;
+                $($assign_trait)*::$assign_fn(&mut lhs, rhs);
+                lhs
+            } $($generic_params)*);
+        );
+    );
+
+    // Where clauses can't be matched in place, so split them off and try again
+    ([$($assign_trait:tt)*], $assign_fn:ident, $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_reuse_internal, [[$($assign_trait)*], $assign_fn, $op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
+    );
+
+    // Binary ops with a borrowed LHS need the matching assignment operator
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:ty, $($tail:tt)*) => (
+        $crate::_parse_binary_assignment_op!($op, $crate::_impl_op_ex_reuse_internal, $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref $lhs_lt $lhs, $($tail)*);
+    );
+    // An owned LHS is only ever used once, so there's nothing to reuse
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, $($tail:tt)*) => (
+        $crate::_impl_op_ex_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, $($tail)*);
+    );
+    ($op:tt $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, not_reusable $op);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_mut_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
+    );
+
+    // The `&mut` variants reborrow their operands and call the impls for shared borrows
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own &mut $lhs, ref [$($rhs_lt)?] $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&*lhs, rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own &mut $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&*lhs, rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own &mut $lhs, own &mut $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&*lhs, &*rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, ref [$($lhs_lt)?] $lhs, own &mut $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &*rhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own &mut $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &*rhs)); out } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own &mut $lhs, own $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (&*lhs, rhs)); out } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $lhs, own &mut $rhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call out = (lhs, &*rhs)); out } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_unary_op!($op, own &mut $lhs, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_unary_op!($op, @call out = (&*lhs)); out } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_assignment_op!($op, $lhs, own &mut $rhs, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_assignment_op!($op, @call (lhs, &*rhs)); } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| $body:block $($generic_params:tt)*) => ();
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+
+    // Where clauses can't be matched in place, so split them off and try again
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_mut_internal, [@variants $op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
+    );
+
+    // Dereferencing is told apart from multiplication by its single argument
+    (* [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_mut_internal!(@error unsupported_mode *);
+    );
+    (* [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_mut_internal!(@error unsupported_mode *);
+    );
+    (Deref $($tail:tt)*) => (
+        $crate::_impl_op_ex_mut_internal!(@error unsupported_mode Deref);
+    );
+    ($op:tt $($args:tt)*) => (
+        $crate::_parse_comparison_op!($op, @if {
+            $crate::_impl_op_ex_mut_internal!(@error unsupported_mode $op);
+        } {
+            $crate::_parse_index_op!($op, @if {
+                $crate::_impl_op_ex_mut_internal!(@error unsupported_mode $op);
+            } {
+                $crate::_impl_op_ex_internal!($op $($args)*);
+                $crate::_impl_op_ex_mut_internal!(@variants $op $($args)*);
+            });
+        });
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_reuse_mut_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
+    );
+    ($($args:tt)*) => (
+        $crate::_impl_op_ex_reuse_internal!($($args)*);
+        $crate::_impl_op_ex_mut_internal!(@variants $($args)*);
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_ptr_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
+    );
+
+    // Each borrowed operand can also be a `Box`, `Rc` or `Arc`, which is dereferenced to call the
+    // impl running the body. The orphan rules only allow `Rc` and `Arc` next to a local type, and
+    // `Rc<LHS>` would need the RHS to be local, which it often isn't (e.g. `&i32`). So they're only
+    // generated for the RHS, and only next to a borrowed LHS, which is assumed to be local. Operands
+    // with named lifetimes are left alone, like the owned variants
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_impl_op_ex_ptr_internal!(@lhs [$op [$($impl_attrs)*] [$(#[$attrs])*] $out, lhs, rhs, [$($generic_params)*]]
+                [[[own $lhs] (&lhs) plain] [[ref [] $lhs] (lhs) plain] [[own $crate::__alloc::Box<$lhs>] (&*lhs) boxed]]
+                [[[own $rhs] (&rhs) plain] [[ref [] $rhs] (rhs) plain] [[own $crate::__alloc::Box<$rhs>] (&*rhs) boxed] [[own $crate::__alloc::Rc<$rhs>] (&*rhs) shared] [[own $crate::__alloc::Arc<$rhs>] (&*rhs) shared]]);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_impl_op_ex_ptr_internal!(@lhs [$op [$($impl_attrs)*] [$(#[$attrs])*] $out, lhs, rhs, [$($generic_params)*]] [[[own $lhs] (&lhs) plain] [[ref [] $lhs] (lhs) plain] [[own $crate::__alloc::Box<$lhs>] (&*lhs) boxed]] [[[own $rhs] (rhs) plain]]);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_impl_op_ex_ptr_internal!(@lhs [$op [$($impl_attrs)*] [$(#[$attrs])*] $out, lhs, rhs, [$($generic_params)*]] [[[own $lhs] (lhs) plain]] [[[own $rhs] (&rhs) plain] [[ref [] $rhs] (rhs) plain] [[own $crate::__alloc::Box<$rhs>] (&*rhs) boxed]]);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+    // The orphan rules only allow `Box` for unary operators, since `Self` is the only type
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_unary_op!($op, own $crate::__alloc::Box<$lhs>, $out, lhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_unary_op!($op, @call out = (&*lhs)); out } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| $body:block $($generic_params:tt)*) => (
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_assignment_op!($op, $lhs, own $crate::__alloc::Box<$rhs>, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_assignment_op!($op, @call (lhs, &*rhs)); } $($generic_params)*);
+            $crate::_parse_assignment_op!($op, $lhs, own $crate::__alloc::Rc<$rhs>, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_assignment_op!($op, @call (lhs, &*rhs)); } $($generic_params)*);
+            $crate::_parse_assignment_op!($op, $lhs, own $crate::__alloc::Arc<$rhs>, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_assignment_op!($op, @call (lhs, &*rhs)); } $($generic_params)*);
+        );
+    );
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| $body:block $($generic_params:tt)*) => ();
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => ();
+
+    // Where clauses can't be matched in place, so split them off and try again
+    (@variants $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_ptr_internal, [@variants $op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
+    );
+
+    // Every pair of forms gets an impl, except for the ones `impl_op_ex!` already generates. The
+    // names the forms use for the operands are passed along with the rest of the context, since
+    // they're bound by the impl
+    (@lhs $ctx:tt [$lhs:tt $($lhs_tail:tt)*] $rhs:tt) => (
+        $crate::_impl_op_ex_ptr_internal!(@rhs $ctx $lhs $rhs);
+        $crate::_impl_op_ex_ptr_internal!(@lhs $ctx [$($lhs_tail)*] $rhs);
+    );
+    (@lhs $ctx:tt [] $rhs:tt) => ();
+    (@rhs $ctx:tt $lhs:tt [$rhs:tt $($rhs_tail:tt)*]) => (
+        $crate::_impl_op_ex_ptr_internal!(@pair $ctx $lhs $rhs);
+        $crate::_impl_op_ex_ptr_internal!(@rhs $ctx $lhs [$($rhs_tail)*]);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out {$body} (&lhs, rhs)} $($generic_params)*);
+    (@rhs $ctx:tt $lhs:tt []) => ();
+    (@pair $ctx:tt [$lhs:tt $lhs_expr:tt plain] [$rhs:tt $rhs_expr:tt plain]) => ();
+    (@pair [$op:tt $impl_attrs:tt [$($attrs:tt)*] $out:ty, $lhs_i:ident, $rhs_i:ident, [$($generic_params:tt)*]] [[$($lhs:tt)*] ($($lhs_expr:tt)*) $lhs_kind:ident] [[$($rhs:tt)*] ($($rhs_expr:tt)*) $rhs_kind:ident]) => (
+        $crate::_parse_binary_op!($op, $($lhs)*, $($rhs)*, $out, $lhs_i, $rhs_i, $impl_attrs $($attrs)* { $crate::_parse_binary_op!($op, @call out = ($($lhs_expr)*, $($rhs_expr)*)); out } $($generic_params)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : $rhs| -> $out {$body} (&lhs, rhs)} $($generic_params)*);
+
+    // Dereferencing is told apart from multiplication by its single argument
+    (* [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_ptr_internal!(@error unsupported_mode *);
+    );
+    (* [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_ptr_internal!(@error unsupported_mode *);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (lhs, &rhs)} $($generic_params)*);
+    (Deref $($tail:tt)*) => (
+        $crate::_impl_op_ex_ptr_internal!(@error unsupported_mode Deref);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $lhs, $rhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (lhs, &rhs)} $($generic_params)*);
+    ($op:tt $($args:tt)*) => (
+        $crate::_parse_comparison_op!($op, @if {
+            $crate::_impl_op_ex_ptr_internal!(@error unsupported_mode $op);
+        } {
+            $crate::_parse_index_op!($op, @if {
+                $crate::_impl_op_ex_ptr_internal!(@error unsupported_mode $op);
+            } {
+                $crate::_impl_op_ex_internal!($op $($args)*);
+                $crate::_impl_op_ex_ptr_internal!(@variants $op $($args)*);
+            });
+        });
+    );
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_ptr_internal {
+    ($($args:tt)*) => {
+        compile_error!("`impl_op_ex!(op ptr ...)` needs the `alloc` feature of `auto_ops`");
+    };
+}
+
+/// `impl_op_ex!(op fold(IDENTITY) ...)` passes the identity to `_generic_params_shifter_internal`
+/// as a `[@fold (IDENTITY)]` group in front of the operator, which it starts the `impl` attributes
+/// with, so it arrives here at the front of them. It's moved in front of everything before the
+/// closure is matched, where it can't get in the way of splitting off the where clause.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_fold_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex, $($tail)*);
     );
 
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
+    // The owned and borrowed items are folded with the impls for `LHS` and `RHS` or `&RHS`, so both
+    // operands have to be borrowed, without named lifetimes that would keep those impls from being generated
+    (@fold $identity:tt $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [] $lhs:ty, [$($rhs_i:tt)*]: ref [] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [] $lhs, [$($rhs_i)*]: ref [] $rhs| -> $out $body $($generic_params)*);
+        $crate::_parse_fold_op!($op, $lhs, $identity, [$($impl_attrs)*] $($generic_params)*);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
+
+    // Where clauses can't be matched in place, so split them off and try again
+    (@fold $identity:tt $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [] $lhs:ty, [$($rhs_i:tt)*]: ref [] $rhs:ty| $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_fold_internal, [@fold $identity $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [] $lhs, [$($rhs_i)*]: ref [] $rhs|] $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
+    (@fold $identity:tt $op:tt $($tail:tt)*) => (
+        $crate::_impl_op_ex_fold_internal!(@error not_foldable $op);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
+
+    ($op:tt [@fold $identity:tt $($impl_attrs:tt)*] $($tail:tt)*) => (
+        $crate::_parse_fold_op!($op, @if {
+            $crate::_impl_op_ex_fold_internal!(@fold $identity $op [$($impl_attrs)*] $($tail)*);
+        } {
+            $crate::_impl_op_ex_fold_internal!(@error not_foldable $op);
+        });
     );
 }
 
@@ -405,6 +1334,8 @@ macro_rules! _impl_op_ex_internal {
 /// impl_op!(op |a: LHS, b: RHS| -> OUT {...});
 /// impl_op!(op |a: RHS, b: LHS| -> OUT {...});
 /// ```
+/// where the second impl calls the first instead of repeating the body.
+///
 /// Make sure that LHS != RHS, and that the operator you are trying to overload is a commutative one.
 /// See the examples for what happens when you try `impl_op_commutative!` on the `-` operator (which isn't usually commutative).
 /// # Examples
@@ -438,6 +1369,12 @@ macro_rules! _impl_op_ex_internal {
 /// }
 #[macro_export]
 macro_rules! impl_op_commutative {
+    (trait $($args:tt)*) => {
+        $crate::_trait_op_internal!([$crate::_impl_op_commutative_internal] [] $($args)*);
+    };
+    ([$($ops:tt),+ $(,)?] $($args:tt)*) => {
+        $crate::_op_list_internal!($crate::impl_op_commutative, [] [$($ops),+] $($args)*);
+    };
     // For some reason $(,)? doesn't work here
     ($op:tt , $($args:tt)*) => {
         $crate::_generic_params_shifter_internal!($crate::_impl_op_commutative_internal, $op $($args)*);
@@ -450,43 +1387,50 @@ macro_rules! impl_op_commutative {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_op_commutative_internal {
-    // Binary Ops Only
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, &$rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, $($tail)*);
+    );
+
+    // The index is never the type being indexed, so index operators can't be commutative
+    ([] $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, not_commutative []);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    (Index $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, not_commutative Index);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+
+    // Binary Ops Only
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_parse_binary_op!($op, ref [$($rhs_lt)?] $rhs, ref [$($lhs_lt)?] $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, &$rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_parse_binary_op!($op, own $rhs, ref [$($lhs_lt)?] $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, &$rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_parse_binary_op!($op, ref [$($rhs_lt)?] $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_parse_binary_op!($op, own $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
     );
 
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, mut $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    // Unary and assignment operators can't be commutative
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, not_commutative $op);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, not_commutative $op);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, mut $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_commutative, not_commutative $op);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
+
+    // Where clauses can't be matched in place, so split them off and try again
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_commutative_internal, [$op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
     );
 }
 
@@ -514,6 +1458,10 @@ macro_rules! _impl_op_commutative_internal {
 /// impl_op!(op |a: RHS, b: &LHS| -> OUT {...});
 /// impl_op!(op |a: RHS, b: LHS| -> OUT {...});
 /// ```
+/// where every impl besides the first calls it instead of repeating the body.
+///
+/// With `mut` after the operator, the borrowed operands also get `&mut` variants (see
+/// [Mutable borrows](index.html#mutable-borrows)).
 /// # Examples
 /// ```
 /// use auto_ops::impl_op_ex_commutative;
@@ -570,6 +1518,15 @@ macro_rules! _impl_op_commutative_internal {
 /// }
 #[macro_export]
 macro_rules! impl_op_ex_commutative {
+    (trait $($args:tt)*) => {
+        $crate::_trait_op_internal!([$crate::_impl_op_ex_commutative_internal] [] $($args)*);
+    };
+    ([$($ops:tt),+ $(,)?] $($args:tt)*) => {
+        $crate::_op_list_internal!($crate::impl_op_ex_commutative, [] [$($ops),+] $($args)*);
+    };
+    ($op:tt $(,)? mut $($args:tt)*) => {
+        $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_commutative_mut_internal, $op $($args)*);
+    };
     // For some reason $(,)? doesn't work here
     ($op:tt , $($args:tt)*) => {
         $crate::_generic_params_shifter_internal!($crate::_impl_op_ex_commutative_internal, $op $($args)*);
@@ -582,75 +1539,1569 @@ macro_rules! impl_op_ex_commutative {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_op_ex_commutative_internal {
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_ex!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, $($tail)*);
+    );
+
+    // The index is never the type being indexed, so index operators can't be commutative
+    ([] $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative []);
+    );
+    (Index $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative Index);
+    );
 
-        $crate::_parse_binary_op!($op, &$rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, &$rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (&rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, &lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : &$rhs| -> $out {$body} (&rhs, &lhs)} $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+
+        $crate::_parse_binary_op!($op, ref [$($rhs_lt)?] $rhs, ref [$($lhs_lt)?] $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, ref [$($rhs_lt)?] $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (&rhs, lhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $rhs, ref [$($lhs_lt)?] $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, &lhs)); out } $($generic_params)*);
+        );
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)? $($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (&rhs, &lhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+
+        $crate::_parse_binary_op!($op, own $rhs, ref [$($lhs_lt)?] $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($lhs_lt)?]
+            $crate::_parse_binary_op!($op, own $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (&rhs, lhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+
+        $crate::_parse_binary_op!($op, ref [$($rhs_lt)?] $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, lhs)); out } $($generic_params)*);
+        $crate::_unless_named_lifetimes_internal!([$($rhs_lt)?]
+            $crate::_parse_binary_op!($op, own $rhs, own $lhs, $out, lhs, rhs, [$($impl_attrs)*] $(#[$attrs])* { $crate::_parse_binary_op!($op, @call_swapped out = (rhs, &lhs)); out } $($generic_params)*);
+        );
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+    );
+
+    // Unary and assignment operators can't be commutative
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative $op);
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative $op);
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative $op);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_ex!($op $(#[$attrs])* |$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
 
-        $crate::_parse_binary_op!($op, $rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, mut $rhs_i : $rhs| -> $out {$body} (&rhs, lhs)} $($generic_params)*);
+    // Where clauses can't be matched in place, so split them off and try again
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_commutative_internal, [$op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : &$lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_ex!($op $(#[$attrs])* |$lhs_i : &$lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
+}
 
-        $crate::_parse_binary_op!($op, $rhs, &$lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : $rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : &$lhs, $rhs_i : $rhs| -> $out {$body} (&rhs, lhs)} $($generic_params)*);
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_ex_commutative_mut_internal {
+    (@error $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_ex!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
 
-        $crate::_parse_binary_op!($op, &$rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|mut $lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, &lhs)} $($generic_params)*);
+    // The index is never the type being indexed, so index operators can't be commutative
+    ([] $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative []);
+    );
+    (Index $($tail:tt)*) => (
+        $crate::_impl_op_error_internal!(impl_op_ex_commutative, not_commutative Index);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : &$rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_ex!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : &$rhs| -> $out $body $($generic_params)*);
 
-        $crate::_parse_binary_op!($op, &$rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, lhs)} $($generic_params)*);
-        $crate::_parse_binary_op!($op, $rhs, $lhs, $out, lhs, rhs, $(#[$attrs])* {|$lhs_i : $lhs, $rhs_i : &$rhs| -> $out {$body} (rhs, &lhs)} $($generic_params)*);
+    // The reversed impls get the same `&mut` variants, with the operands swapped
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_parse_comparison_op!($op, @if {
+            $crate::_impl_op_ex_commutative_mut_internal!(@error unsupported_mode $op);
+        } {
+            $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+            $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+            $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($rhs_i)*]: ref [$($rhs_lt)?] $rhs, [$($lhs_i)*]: ref [$($lhs_lt)?] $lhs| -> $out $body $($generic_params)*);
+        });
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
+        $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($rhs_i)*]: own $rhs, [$($lhs_i)*]: ref [$($lhs_lt)?] $lhs| -> $out $body $($generic_params)*);
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: ref [$($rhs_lt:lifetime)?] $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: ref [$($rhs_lt)?] $rhs| -> $out $body $($generic_params)*);
+        $crate::_impl_op_ex_mut_internal!(@variants $op [$($impl_attrs)*] $(#[$attrs])* |[$($rhs_i)*]: ref [$($rhs_lt)?] $rhs, [$($lhs_i)*]: own $lhs| -> $out $body $($generic_params)*);
+    );
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty, [$($rhs_i:tt)*]: own $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs, [$($rhs_i)*]: own $rhs| -> $out $body $($generic_params)*);
     );
 
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_commutative!($op $(#[$attrs])* |mut $lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
+    // Unary and assignment operators are reported by `impl_op_ex_commutative!` itself
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $($tail:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |mut $lhs_i:ident : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_commutative!($op $(#[$attrs])* |mut $lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: own $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: own $lhs| $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, mut $rhs_i:ident : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_commutative!($op $(#[$attrs])* |$lhs_i : $lhs, mut $rhs_i : $rhs| -> $out $body $($generic_params)*);
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: ref [$($lhs_lt:lifetime)?] $lhs:ty| $($tail:tt)*) => (
+        $crate::_impl_op_ex_commutative_internal!($op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: ref [$($lhs_lt)?] $lhs| $($tail)*);
     );
-    ($op:tt $(#[$attrs:meta])* |$lhs_i:tt : $lhs:ty, $rhs_i:tt : $rhs:ty| -> $out:ty $body:block $($generic_params:tt)*) => (
-        $crate::impl_op_commutative!($op $(#[$attrs])* |$lhs_i : $lhs, $rhs_i : $rhs| -> $out $body $($generic_params)*);
+
+    // Where clauses can't be matched in place, so split them off and try again
+    ($op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($tail:tt)*) => (
+        $crate::_where_clause_shifter_internal!($crate::_impl_op_ex_commutative_mut_internal, [$op [$($impl_attrs)*] $(#[$attrs])* |] $($tail)*);
     );
 }
 
-/// This helper allows us to put the generic parameters in a place that's ergonomic (just before
-/// the lambda), but still be able to match things after them. The problem with matching things
-/// where they are is we *need* to use a `tt*` to capture the generic params in order to allow
-/// arbitrary params and constraints, but that will eat the rest of the input (there's no way to
-/// limit the kinds of tokens it will consume, and the macro matching engine prevents against
-/// arbitrarily long back-tracking). Therefore the `tt*` *must* be at the end of any rule that tries
-/// to actually match anything useful.
+/// Implements several operators at once, sharing generic params, attributes and a where clause
+/// between them.
 ///
-/// To accomplish this, we semantically match the tokens before the generic parameters, then use the
-/// tt muncher pattern to shift the tokens representing the generic params to the end of the token
-/// stream. We use the assumption that that `|` (a token that is just the pipe character) will never
-/// show up in generic parameters, and stop shifting as soon as we encounter it. We then invokes the
-/// `callback` macro with the shifted arguments.
+/// The block starts with an optional header ending in `;`, which can have fn attributes, `impl`
+/// attributes, generic params, a `for Type` scope and a where clause, in that order. The rest of
+/// the block is a `;` separated list of `impl_op!`, `impl_op_ex!`, `impl_op_commutative!` and
+/// `impl_op_ex_commutative!` invocations, which take the same arguments as the macros themselves.
+/// Generic params given to an entry are added to the shared ones, and `Self` in its signature is
+/// replaced with the `for` type.
+///
+/// See the [module level documentation](index.html) for more information.
+///
+/// ```
+/// use auto_ops::impl_ops;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Barrel<T> {
+///     pub bananas: T,
+/// }
+///
+/// impl_ops! {
+///     #[inline] <T: Copy> for Barrel<T> where T: ::core::ops::Add<Output = T>;
+///
+///     impl_op_ex!(+ |a: &Self, b: &Self| -> Self { Barrel { bananas: a.bananas + b.bananas } });
+///     impl_op_ex!(+ |a: &Self, b: T| -> Self { Barrel { bananas: a.bananas + b } });
+///     impl_op!(+= |a: &mut Self, b: Self| { a.bananas = a.bananas + b.bananas; });
+/// }
+///
+/// fn main() {
+///     let mut barrel = Barrel { bananas: 1 } + &Barrel { bananas: 2 };
+///     assert_eq!(Barrel { bananas: 3 }, barrel);
+///     assert_eq!(Barrel { bananas: 4 }, &barrel + 1);
+///     barrel += Barrel { bananas: 2 };
+///     assert_eq!(Barrel { bananas: 5 }, barrel);
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_ops {
+    ($($t:tt)*) => {
+        $crate::_impl_ops_internal!(@attrs [] [] $($t)*);
+    };
+}
+
+/// Parses the header of an `impl_ops!` block, then hands each entry to the engine of the macro it
+/// names. The shared attributes are passed to `_generic_params_shifter_internal` as if they had
+/// been given to the entry, along with the shared where clause and generic params, which it puts in
+/// front of the ones it collects (see its `@context` rules) so they end up after the closure like
+/// those of a regular invocation.
+///
+/// The shared generic params are kept without their angle brackets, so that entries with their own
+/// generic params can have both in the same list.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! _generic_params_shifter_internal {
-    // Once we've hit a `|`, use the final rule
-    ($callback:path, $op:tt $(#[$attrs:meta])* | $($tail:tt)*) => {
-        $callback!($op $(#[$attrs])* | $($tail)*);
+macro_rules! _impl_ops_internal {
+    // Header attributes, split the same way as `_generic_params_shifter_internal` does
+    (@attrs [$($attrs:tt)*] [$($impl_attrs:tt)*] #[$attr:meta] $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@attrs [$($attrs)* #[$attr]] [$($impl_attrs)*] $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] [$($impl_attrs:tt)*] impl #[$attr:meta] $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@impl_attrs [$($attrs)*] [$($impl_attrs)* #[$attr]] $($tail)*);
+    };
+    (@impl_attrs [$($attrs:tt)*] [$($impl_attrs:tt)*] #[$attr:meta] $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@impl_attrs [$($attrs)*] [$($impl_attrs)* #[$attr]] $($tail)*);
+    };
+    (@attrs $attrs:tt $impl_attrs:tt < $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@inner $crate::_impl_ops_internal, [@scope $attrs $impl_attrs] $($tail)*);
+    };
+    (@impl_attrs $attrs:tt $impl_attrs:tt < $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@inner $crate::_impl_ops_internal, [@scope $attrs $impl_attrs] $($tail)*);
+    };
+    (@attrs $attrs:tt $impl_attrs:tt $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@scope $attrs $impl_attrs [] $($tail)*);
+    };
+    (@impl_attrs $attrs:tt $impl_attrs:tt $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@scope $attrs $impl_attrs [] $($tail)*);
+    };
+
+    // The rest of the header: `for Type`, a where clause, or neither
+    (@scope $attrs:tt $impl_attrs:tt [$($generic_params:tt)*] for $scope:ty ; $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@entries [$attrs $impl_attrs [[] [$($generic_params)*]] [$scope]] $($tail)*);
+    };
+    (@scope $attrs:tt $impl_attrs:tt $generic_params:tt for $scope:ty where $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@where $attrs $impl_attrs $generic_params [$scope]] [;] [] [] $($tail)*);
+    };
+    (@scope $attrs:tt $impl_attrs:tt $generic_params:tt where $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@where $attrs $impl_attrs $generic_params []] [;] [] [] $($tail)*);
+    };
+    (@scope $attrs:tt $impl_attrs:tt [$($generic_params:tt)*] ; $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@entries [$attrs $impl_attrs [[] [$($generic_params)*]] []] $($tail)*);
+    };
+    (@scope $attrs:tt $impl_attrs:tt [$($generic_params:tt)*] $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@entries [$attrs $impl_attrs [[] [$($generic_params)*]] []] $($tail)*);
+    };
+
+    // The where clause runs until the `;` ending the header. A trailing comma is dropped, since the
+    // where clause of each entry is appended after a comma
+    (@where $attrs:tt $impl_attrs:tt [$($generic_params:tt)*] $scope:tt [$($where_clause:tt)*] , ; $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@entries [$attrs $impl_attrs [[$($where_clause)*] [$($generic_params)*]] $scope] $($tail)*);
+    };
+    (@where $attrs:tt $impl_attrs:tt [$($generic_params:tt)*] $scope:tt [$($where_clause:tt)*] ; $($tail:tt)*) => {
+        $crate::_impl_ops_internal!(@entries [$attrs $impl_attrs [[$($where_clause)*] [$($generic_params)*]] $scope] $($tail)*);
+    };
+    (@where $attrs:tt $impl_attrs:tt $generic_params:tt $scope:tt [$($where_clause:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@where $attrs $impl_attrs $generic_params $scope] [;] [] [$($where_clause)* $next] $($tail)*);
+    };
+
+    (@entries [$attrs:tt $impl_attrs:tt $seed:tt $scope:tt] $($mac:ident ! ($($args:tt)*));* $(;)?) => {
+        $($crate::_find_marker_internal!($crate::_impl_ops_internal, [@self [$mac [$attrs $impl_attrs $seed $scope]] $scope []] [] $scope [] $($args)*);)*
+    };
+
+    // `Self` is replaced with the `for` type everywhere in the signature, including inside of
+    // groups, which are descended into by pushing what's left of the outer group onto a stack. The
+    // body (the `{...}` group ending the entry, since type lists are braced too) is left alone, and
+    // so are paths starting with `Self ::`, like the ones delegated to, and the operator list or
+    // index operator that an entry can start with
+    (@self $k:tt $scope:tt [$($stack:tt)*] [$($done:tt)*] ($($group:tt)*) $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [[paren [$($done)*] [$($tail)*]] $($stack)*]] [] $scope [] $($group)*);
+    };
+    (@self $k:tt $scope:tt [] [] [$($ops:tt)*] $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope []] [] $scope [[$($ops)*]] $($tail)*);
+    };
+    (@self $k:tt $scope:tt [$($stack:tt)*] [$($done:tt)*] [$($group:tt)*] $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [[bracket [$($done)*] [$($tail)*]] $($stack)*]] [] $scope [] $($group)*);
+    };
+    (@self $k:tt $scope:tt [] [$($done:tt)*] {$($body:tt)*}) => {
+        $crate::_impl_ops_internal!(@dispatch $k $($done)* {$($body)*});
+    };
+    (@self $k:tt $scope:tt [$($stack:tt)*] [$($done:tt)*] {$($group:tt)*} $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [[brace [$($done)*] [$($tail)*]] $($stack)*]] [] $scope [] $($group)*);
+    };
+    (@self $k:tt $scope:tt [[paren [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [$($stack)*]] [] $scope [$($outer)* ($($done)*)] $($tail)*);
+    };
+    (@self $k:tt $scope:tt [[bracket [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [$($stack)*]] [] $scope [$($outer)* [$($done)*]] $($tail)*);
+    };
+    (@self $k:tt $scope:tt [[brace [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_impl_ops_internal, [@self $k $scope [$($stack)*]] [] $scope [$($outer)* {$($done)*}] $($tail)*);
+    };
+    (@self $k:tt $scope:tt [] [$($done:tt)*]) => {
+        $crate::_impl_ops_internal!(@dispatch $k $($done)*);
+    };
+
+    (@dispatch [impl_op $ctx:tt] $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_internal] $ctx $($args)*);
+    };
+    (@dispatch [impl_op_ex $ctx:tt] $op:tt $(,)? reuse mut $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_reuse_mut_internal] $ctx $op $($args)*);
+    };
+    (@dispatch [impl_op_ex $ctx:tt] $op:tt $(,)? reuse $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_reuse_internal] $ctx $op $($args)*);
+    };
+    (@dispatch [impl_op_ex $ctx:tt] $op:tt $(,)? mut $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_mut_internal] $ctx $op $($args)*);
+    };
+    (@dispatch [impl_op_ex $ctx:tt] $op:tt $(,)? ptr $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_ptr_internal] $ctx $op $($args)*);
+    };
+    (@dispatch [impl_op_ex [$attrs:tt [$($impl_attrs:tt)*] $seed:tt $scope:tt]] $op:tt $(,)? fold($identity:expr) $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_fold_internal] [$attrs [@fold ($identity) $($impl_attrs)*] $seed $scope] $op $($args)*);
+    };
+    (@dispatch [impl_op_ex $ctx:tt] $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_internal] $ctx $($args)*);
+    };
+    (@dispatch [impl_op_commutative $ctx:tt] $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_commutative_internal] $ctx $($args)*);
+    };
+    (@dispatch [impl_op_ex_commutative $ctx:tt] $op:tt $(,)? mut $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_commutative_mut_internal] $ctx $op $($args)*);
+    };
+    (@dispatch [impl_op_ex_commutative $ctx:tt] $($args:tt)*) => {
+        $crate::_impl_ops_internal!(@route [$crate::_impl_op_ex_commutative_internal] $ctx $($args)*);
+    };
+    (@dispatch [$mac:ident $ctx:tt] $($args:tt)*) => {
+        compile_error!(concat!(
+            "`impl_ops!`: expected `impl_op!`, `impl_op_ex!`, `impl_op_commutative!` or `impl_op_ex_commutative!`, found `",
+            stringify!($mac),
+            "!`"
+        ));
+    };
+
+    // The same forms as the public macros accept
+    (@route $engine:tt $ctx:tt [$($ops:tt),+ $(,)?] $($tail:tt)*) => {
+        $crate::_op_list_internal!($crate::_impl_ops_internal, [@route $engine $ctx] [$($ops),+] $($tail)*);
+    };
+    (@route [$engine:path] [$attrs:tt $impl_attrs:tt $seed:tt $scope:tt] trait $($tail:tt)*) => {
+        $crate::_trait_op_internal!([@context $attrs $impl_attrs $seed $engine] [] $($tail)*);
+    };
+    (@route [$engine:path] [$attrs:tt $impl_attrs:tt $seed:tt $scope:tt] $op:tt , $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@context $attrs $impl_attrs $seed $engine, $op $($tail)*);
+    };
+    (@route [$engine:path] [$attrs:tt $impl_attrs:tt $seed:tt $scope:tt] $op:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@context $attrs $impl_attrs $seed $engine, $op $($tail)*);
+    };
+}
+/// Implements a binary operator for every pair of a list of LHS types and a list of RHS types,
+/// with the same body. Both operands are taken by reference, and the owned variants are generated
+/// the same way as with [`impl_op_ex!`](macro.impl_op_ex.html). Pairs in the optional `skip` list
+/// are left out, e.g. because they already have hand-written impls.
+///
+/// Each type has to be a single token tree, so types made of several tokens need to be wrapped in
+/// parens, like `(Wrapper<f32>)`. The same goes for the `skip` list, whose pairs are compared token
+/// by token with the listed types.
+///
+/// See the [module level documentation](index.html) for more information.
+///
+/// ```
+/// use auto_ops::impl_op_matrix;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Meters(f64);
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Feet(f64);
+///
+/// trait ToMeters {
+///     fn meters(&self) -> f64;
+/// }
+/// impl ToMeters for Meters {
+///     fn meters(&self) -> f64 { self.0 }
+/// }
+/// impl ToMeters for Feet {
+///     fn meters(&self) -> f64 { self.0 * 0.3048 }
+/// }
+///
+/// impl_op_matrix!(+ [Meters, Feet] x [Meters, Feet] skip [(Meters, Meters)] |a, b| -> Meters {
+///     Meters(a.meters() + b.meters())
+/// });
+/// auto_ops::impl_op_ex!(+ |a: &Meters, b: &Meters| -> Meters { Meters(a.0 + b.0) });
+///
+/// fn main() {
+///     assert_eq!(Meters(3.0), Meters(1.0) + &Meters(2.0));
+///     assert_eq!(Meters(1.6096), &Meters(1.0) + Feet(2.0));
+///     assert_eq!(Meters(0.6096), Feet(1.0) + Feet(1.0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_op_matrix {
+    ($op:tt [$($lhs:tt),+ $(,)?] x [$($rhs:tt),+ $(,)?] skip [$($skip:tt),* $(,)?] $($closure:tt)*) => {
+        $crate::_op_matrix_internal!(@lhs [$op [$($skip)*] [$($closure)*]] [$($lhs)*] [$($rhs)*]);
+    };
+    ($op:tt [$($lhs:tt),+ $(,)?] x [$($rhs:tt),+ $(,)?] $($closure:tt)*) => {
+        $crate::_op_matrix_internal!(@lhs [$op [] [$($closure)*]] [$($lhs)*] [$($rhs)*]);
+    };
+}
+
+/// Goes through every `(lhs, rhs)` pair of `impl_op_matrix!`, and for each one defines a local
+/// macro with a rule matching each skipped pair (which expands to nothing), followed by one
+/// matching the pair itself (which expands to the impls), then invokes it with the pair. This
+/// compares the pair against the skip list without a rule that has to match arbitrary types.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _op_matrix_internal {
+    (@lhs $ctx:tt [$lhs:tt $($lhs_tail:tt)*] $rhs:tt) => {
+        $crate::_op_matrix_internal!(@rhs $ctx $lhs $rhs);
+        $crate::_op_matrix_internal!(@lhs $ctx [$($lhs_tail)*] $rhs);
+    };
+    (@lhs $ctx:tt [] $rhs:tt) => {};
+    (@rhs $ctx:tt $lhs:tt [$rhs:tt $($rhs_tail:tt)*]) => {
+        $crate::_op_matrix_internal!(@pair $ctx $lhs $rhs);
+        $crate::_op_matrix_internal!(@rhs $ctx $lhs [$($rhs_tail)*]);
+    };
+    (@rhs $ctx:tt $lhs:tt []) => {};
+
+    (@pair [$op:tt [$($skip:tt)*] [$($closure:tt)*]] $lhs:tt $rhs:tt) => {
+        macro_rules! __auto_ops_matrix {
+            $($skip => {};)*
+            ($lhs, $rhs) => {
+                $crate::_op_matrix_internal!(@closure $op $lhs $rhs $($closure)*);
+            };
+        }
+        __auto_ops_matrix!($lhs, $rhs);
+    };
+
+    (@closure $op:tt $lhs:tt $rhs:tt $(#[$attrs:meta])* $(impl $(#[$impl_attrs:meta])+)? |$lhs_pat:pat, $rhs_pat:pat| $($tail:tt)*) => {
+        $crate::impl_op_ex!($op $(#[$attrs])* $(impl $(#[$impl_attrs])+)? |$lhs_pat: &$lhs, $rhs_pat: &$rhs| $($tail)*);
+    };
+    (@closure $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(impl_op_matrix, matrix_closure $op);
+    };
+}
+
+/// Moves tokens into `[$($done)*]` until the next group or marker, then invokes
+/// `$callback!($($args)* [$($done)*] rest...)` with `rest` starting at that token, or with nothing
+/// after the group once the tokens run out. Callers handle the token they stopped at, and move it
+/// into `done` themselves before calling this again if it isn't the one they're looking for.
+///
+/// The marker is `[where]`, `[;]`, `[op]`, or `[]` to only stop at groups. For `;` this stops at
+/// the token before it, so that a trailing comma can be dropped. For `op` it stops at the token before every
+/// identifier, which could be the `$` of an `$op` placeholder (`$` can't be matched itself), and
+/// passes the identifier on twice: one to check against `op`, and the user's own to keep, since a
+/// matched `op` would come out as the crate's `op`, which doesn't name the user's metavariable.
+/// Along the way, `Self` is replaced with the tokens of the `[$($self_ty)*]` group if there are
+/// any, except in front of a `::`, where it starts a path that a type can't be spliced into.
+///
+/// Moving one token per step would cost one recursion (and one re-match of the rest of the input)
+/// per token, which adds up to the recursion limit for long generic params and signatures. So each
+/// of the next eight positions is checked in order, and the first one holding a group or a marker
+/// is where this stops. Without one, all eight tokens are moved in a single step.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _find_marker_internal {
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)*] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt ($($group:tt)*) $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] ($($group)*) $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt [$($group:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] [$($group)*] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt {$($group:tt)*} $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] {$($group)*} $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt Self :: $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 Self ::] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)+] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt Self $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)+] [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $($self_ty)+] $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [where] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt where $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] where $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [;] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $before:tt ; $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] $before ; $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [op] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $before:tt $placeholder:ident $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] $before $placeholder $placeholder $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($tail:tt)*) => {
+        $crate::_find_marker_internal!($callback, [$($args)*] [$($marker)*] [$($self_ty)*] [$($done)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($tail)*);
+    };
+    // Fewer than eight tokens are left, and none of them are groups or markers
+    ($callback:path, [$($args:tt)*] [$($marker:tt)*] [$($self_ty:tt)*] [$($done:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [$($done)* $($tail)*]);
+    };
+}
+
+/// This helper allows us to put the generic parameters in a place that's ergonomic (just before
+/// the lambda), but still be able to match things after them. The problem with matching things
+/// where they are is we *need* to use a `tt*` to capture the generic params in order to allow
+/// arbitrary params and constraints, but that will eat the rest of the input (there's no way to
+/// limit the kinds of tokens it will consume, and the macro matching engine prevents against
+/// arbitrarily long back-tracking). Therefore the `tt*` *must* be at the end of any rule that tries
+/// to actually match anything useful.
+///
+/// To accomplish this, we semantically match the tokens before the generic parameters, then use the
+/// tt muncher pattern to collect the tokens representing the generic params into a group. We use the
+/// assumption that that `|` (a token that is just the pipe character) will never show up in generic
+/// parameters, and stop collecting as soon as we encounter it. We then hand the arguments to
+/// `_closure_args_internal` with the generic params moved to the end, which invokes the `callback`
+/// macro.
+///
+/// The generic params can't be captured as a whole instead. Unlike `(...)`, `[...]` and `{...}`,
+/// `<...>` isn't a delimited group, so it isn't a single `tt`, and a `$($gp:tt)*` can't be
+/// followed by the `>` or `|` ending it, since the matcher rejects a `tt` repetition followed by
+/// a token that a `tt` could also match as a local ambiguity. Fragments such as `ty` or `path`
+/// don't cover everything a generic param list can contain either (lifetime bounds, `?Sized`,
+/// `const` params, defaults). So the tokens have to be walked, but moving one token per step would
+/// make long bound lists cost one recursion (and one re-match of the rest of the input) per token.
+/// Tokens are moved eight at a time instead. The rules looking for `|` at each of the next eight
+/// positions are tried first, in order, so the first one that matches has found the earliest `|`.
+///
+/// If no `|` is found, the tokens are either the fn form or the path delegation form, which
+/// `_delegate_internal` takes care of. A `fn (` can't be looked for here like a `|` can, since
+/// generic params can contain fn pointer types, so it's only recognized once the generic params
+/// have been split off by counting angle brackets. A `fn` right after the operator (and its
+/// attributes) can't be part of any generic params, so it's treated the same as a `|`, with the
+/// params moved in between a pair of `|`.
+///
+/// The attributes in front of the generic params are split one at a time into the fn attributes
+/// and the `impl` attributes, and the latter are passed on as a bracketed group right after the
+/// operator, i.e. `$op [#[impl_attr]...] #[fn_attr]... |`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _generic_params_shifter_internal {
+    // Once we've hit a `|`, use the final rules
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)*);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1 $t2);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1 $t2 $t3);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1 $t2 $t3 $t4);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1 $t2 $t3 $t4 $t5);
+    };
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($tail)* $($generic_params)* $t0 $t1 $t2 $t3 $t4 $t5 $t6);
+    };
+    // Until then, move the next eight tokens into the generic params
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@collect $callback, [$($head)*] [$($generic_params)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($tail)*);
+    };
+    // Ran out of tokens without finding a closure, so it should be a path to delegate to
+    (@collect $callback:path, [$($head:tt)*] [$($generic_params:tt)*] $($tail:tt)*) => {
+        $crate::_delegate_internal!($callback, [$($head)*] $($generic_params)* $($tail)*);
+    };
+
+    // Attributes come first, and the ones after `impl` go on the impl blocks rather than the fns
+    (@attrs $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] $seed:tt #[$attr:meta] $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@attrs $callback, $op [$($attrs)* #[$attr]] [$($impl_attrs)*] $seed $($tail)*);
+    };
+    (@attrs $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] $seed:tt impl #[$attr:meta] $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@impl_attrs $callback, $op [$($attrs)*] [$($impl_attrs)* #[$attr]] $seed $($tail)*);
+    };
+    (@impl_attrs $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] $seed:tt #[$attr:meta] $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@impl_attrs $callback, $op [$($attrs)*] [$($impl_attrs)* #[$attr]] $seed $($tail)*);
+    };
+    // Without any generic params there's nothing to collect (besides those of an `impl_ops!` block).
+    // `$plain` is what goes in front of the generic params of the operator, or in their place, and
+    // `$merge` replaces the `<` opening them, so both sets end up in the same list
+    (@$state:ident $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] [[$($plain:tt)*] $merge:tt] | $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$op [$($impl_attrs)*] $($attrs)* |] [] $($tail)* $($plain)*);
+    };
+    (@$state:ident $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] [[$($plain:tt)*] $merge:tt] fn ($($args:tt)*) $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$op [$($impl_attrs)*] $($attrs)* |] [] $($args)* | $($tail)* $($plain)*);
+    };
+    (@$state:ident $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] [$plain:tt [$($merge:tt)*]] < $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@collect $callback, [$op [$($impl_attrs)*] $($attrs)*] [$($merge)*] $($tail)*);
+    };
+    (@$state:ident $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] [[$($plain:tt)*] $merge:tt] $next:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@collect $callback, [$op [$($impl_attrs)*] $($attrs)*] [$($plain)* $next] $($tail)*);
+    };
+    (@$state:ident $callback:path, $op:tt [$($attrs:tt)*] [$($impl_attrs:tt)*] $seed:tt) => {
+        $callback!(@error no_closure $op);
+    };
+
+    // `impl_ops!` passes the attributes shared by the block, its where clause (which ends up in front
+    // of the generic params, see `_where_clause_shifter_internal`) and its generic params without
+    // the angle brackets
+    (@context $attrs:tt $impl_attrs:tt [$where_clause:tt []] $callback:path, $op:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@attrs $callback, $op $attrs $impl_attrs [[$where_clause] [$where_clause <]] $($tail)*);
+    };
+    (@context $attrs:tt $impl_attrs:tt [$where_clause:tt [$($generic_params:tt)+]] $callback:path, $op:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@attrs $callback, $op $attrs $impl_attrs [[$where_clause <$($generic_params)+>] [$where_clause <$($generic_params)+,]] $($tail)*);
+    };
+    // `impl_op_ex!(op fold(IDENTITY) ...)` starts the `impl` attributes with the identity
+    ($callback:path, [@fold $identity:tt] $op:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@attrs $callback, $op [] [@fold $identity] [[] [<]] $($tail)*);
+    };
+    ($callback:path, $op:tt $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!(@attrs $callback, $op [] [] [[] [<]] $($tail)*);
+    };
+}
+
+/// Where clauses come after the closure signature but before its body, so like the generic params
+/// they can't be matched where they are. None of the rules in the `_impl_op_*_internal` macros
+/// match when a where clause is present, so they all fall back to this helper.
+///
+/// It uses the same tt muncher pattern as `_generic_params_shifter_internal`: tokens are moved into
+/// `[$($head)*]` until we hit `where`, and from then on into a second group until we hit the body
+/// (the first `{...}` group, which can't show up in a where clause). The clause is then passed to
+/// the `callback` macro as a `[...]` group placed just before the generic params, which is where
+/// the impl emitters look for it. Like the generic params, tokens are moved eight at a time, after
+/// checking each of the next eight positions for the token we're looking for.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _where_clause_shifter_internal {
+    // The where clause of an `impl_ops!` block ends up in front of the generic params
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] {$($body:tt)*} [] $($generic_params:tt)*) => {
+        $callback!($($head)* {$($body)*} [$($where_clause)*] $($generic_params)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] {$($body:tt)*} [$($block_where_clause:tt)+] $($generic_params:tt)*) => {
+        $callback!($($head)* {$($body)*} [$($block_where_clause)+, $($where_clause)*] $($generic_params)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] {$($body:tt)*} $($generic_params:tt)*) => {
+        $callback!($($head)* {$($body)*} [$($where_clause)*] $($generic_params)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2 $t3] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2 $t3 $t4] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2 $t3 $t4 $t5] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] {$($body)*} $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] @where [$($where_clause:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [$($where_clause)* $next] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)*] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2 $t3] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2 $t3 $t4] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2 $t3 $t4 $t5] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt where $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] @where [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_where_clause_shifter_internal!($callback, [$($head)* $next] $($tail)*);
+    };
+    // Nothing matched even without a where clause, so the closure itself has the wrong shape
+    ($callback:path, [$($head:tt)*]) => {
+        $callback!(@error shape $($head)*);
+    };
+}
+
+/// Closure params are `pattern: type`, but a `pat` fragment can't be followed by `:` and matching
+/// the pattern as `tt`s is ambiguous with the `:` that ends it. This helper munches the tokens of
+/// each pattern into a `[...]` group until it sees the `:` (patterns can only contain `:` inside
+/// of a delimited group), then parses the type.
+///
+/// A parsed `ty` can't be matched against `&` or `&mut` later on, and trying to parse `'a T` as a
+/// `ty` is a hard error rather than a failed match, so the ownership of each param is split out
+/// here and written as a keyword in front of the type: `own T` for `T`, `ref [] T` for `&T`,
+/// `ref ['a] T` for `&'a T` and `mutref T` for `&mut T`. Users (usually other macros) can also
+/// write these keywords themselves, minus the brackets, which is passed through as-is. The
+/// `callback` macro is invoked with the params rewritten as `|[$($pat)*]: <ownership> $ty, ...|`,
+/// followed by the rest of the tokens.
+///
+/// Since this is the first point where we know what kind of operator the closure is for, it also
+/// checks that the operator is of that kind (and that a borrowed output names a lifetime), and
+/// invokes `callback` with `@error <kind> $op` otherwise. For the same reason, `Self` in the output
+/// and a missing output are replaced with the owned lhs type here.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _closure_args_internal {
+    // Index operators return a borrow of `self`, and only the trait's output type is kept
+    (@done $callback:path, [[] $($head:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@index $callback, [[] $($head)*] $($tail)*);
+    };
+    (@done $callback:path, [Index $($head:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@index $callback, [Index $($head)*] $($tail)*);
+    };
+    (@index $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt, $($params:tt)*] -> &mut $($tail:tt)*) => {
+        $callback!(@error index_shape $op);
+    };
+    (@index $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt, $($params:tt)*] -> & $($tail:tt)*) => {
+        $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs, $($params)* | -> $($tail)*);
+    };
+    (@index $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $lhs:tt, $($params:tt)*] -> &mut $($tail:tt)*) => {
+        $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: mutref $lhs, $($params)* | -> $($tail)*);
+    };
+    (@index $callback:path, [$op:tt $($head:tt)*] $($tail:tt)*) => {
+        $callback!(@error index_shape $op);
+    };
+
+    // So does dereferencing, which is told apart from multiplication by its single argument
+    (@done $callback:path, [* [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: own $lhs:tt] $($tail:tt)*) => {
+        $callback!(@error deref_shape *);
+    };
+    (@done $callback:path, [* [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@deref $callback, [* [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs] $($tail)*);
+    };
+    (@done $callback:path, [* [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $lhs:tt] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@deref $callback, [* [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: mutref $lhs] $($tail)*);
+    };
+    (@done $callback:path, [Deref $($head:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@deref $callback, [Deref $($head)*] $($tail)*);
+    };
+    (@deref $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt] -> &mut $($tail:tt)*) => {
+        $callback!(@error deref_shape $op);
+    };
+    (@deref $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt] -> & $($tail:tt)*) => {
+        $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs | -> $($tail)*);
+    };
+    (@deref $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $lhs:tt] -> &mut $($tail:tt)*) => {
+        $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: mutref $lhs | -> $($tail)*);
+    };
+    (@deref $callback:path, [$op:tt $($head:tt)*] $($tail:tt)*) => {
+        $callback!(@error deref_shape $op);
+    };
+
+    // Assignment operators don't have an output
+    (@done $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $($params:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: mutref $($params)*] $($tail)*);
+    };
+    // A missing output means the owned lhs type. Comparison operators have a fixed return type
+    // instead, which is what a missing output means for them
+    (@done $callback:path, [$($head:tt)*] {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@missing $callback, [$($head)*] {$($body)*} $($tail)*);
+    };
+    (@done $callback:path, [$($head:tt)*] where $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@missing $callback, [$($head)*] where $($tail)*);
+    };
+    // `Self` would be the borrowed type in the impls for `&LHS`, so it's replaced with the owned lhs
+    // type everywhere in the output, descending into groups like `_impl_ops_internal` does. The
+    // output ends at the where clause or the body. Borrowed outputs need a named lifetime
+    (@done $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: own $lhs:tt $($params:tt)*] -> &$lt:lifetime $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: own $lhs $($params)*] $lhs []] [where] [$lhs] [&$lt] $($tail)*);
+    };
+    (@done $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt $($params:tt)*] -> &$lt:lifetime $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs $($params)*] $lhs []] [where] [$lhs] [&$lt] $($tail)*);
+    };
+    (@done $callback:path, [$op:tt $($head:tt)*] -> & $($tail:tt)*) => {
+        $callback!(@error borrowed_output $op);
+    };
+    (@done $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: own $lhs:tt $($params:tt)*] -> $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: own $lhs $($params)*] $lhs []] [where] [$lhs] [] $($tail)*);
+    };
+    (@done $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt $($params:tt)*] -> $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs $($params)*] $lhs []] [where] [$lhs] [] $($tail)*);
+    };
+    (@done $callback:path, [$($head:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, [$($head)*] $($tail)*);
+    };
+
+    (@missing $callback:path, [$op:tt $($head:tt)*] $($tail:tt)*) => {
+        $crate::_parse_comparison_op!($op, @if {
+            $crate::_parse_comparison_op!($op, @output $crate::_closure_args_internal, [@check $callback, [$op $($head)*]] $($tail)*);
+        } {
+            $crate::_closure_args_internal!(@self $callback, [$op $($head)*] [$op $($head)*] $($tail)*);
+        });
+    };
+    (@self $callback:path, [$($head:tt)*] [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: own $lhs:tt $($params:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, [$($head)*] -> $lhs $($tail)*);
+    };
+    (@self $callback:path, [$($head:tt)*] [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt $($params:tt)*] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, [$($head)*] -> $lhs $($tail)*);
+    };
+
+    (@output $callback:path, $head:tt $lhs:tt [] [$($done:tt)*] {$($body:tt)*} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, $head -> $($done)* {$($body)*} $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [] [$($done:tt)*] where $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@check $callback, $head -> $($done)* where $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [$($stack:tt)*] [$($done:tt)*] ($($group:tt)*) $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [[paren [$($done)*] [$($tail)*]] $($stack)*]] [where] [$lhs] [] $($group)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [$($stack:tt)*] [$($done:tt)*] [$($group:tt)*] $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [[bracket [$($done)*] [$($tail)*]] $($stack)*]] [where] [$lhs] [] $($group)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [$($stack:tt)*] [$($done:tt)*] {$($group:tt)*} $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [[brace [$($done)*] [$($tail)*]] $($stack)*]] [where] [$lhs] [] $($group)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt $stack:tt [$($done:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs $stack] [where] [$lhs] [$($done)* $next] $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [[paren [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [$($stack)*]] [where] [$lhs] [$($outer)* ($($done)*)] $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [[bracket [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [$($stack)*]] [where] [$lhs] [$($outer)* [$($done)*]] $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [[brace [$($outer:tt)*] [$($tail:tt)*]] $($stack:tt)*] [$($done:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_closure_args_internal, [@output $callback, $head $lhs [$($stack)*]] [where] [$lhs] [$($outer)* {$($done)*}] $($tail)*);
+    };
+    (@output $callback:path, $head:tt $lhs:tt [] [$($done:tt)*]) => {
+        $crate::_closure_args_internal!(@check $callback, $head -> $($done)*);
+    };
+
+    // Make sure the operator is of the right kind for the params: `&mut` for assignment operators,
+    // and otherwise one param for unary operators and two for binary operators
+    (@check $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $($params:tt)*] $($tail:tt)*) => {
+        $crate::_parse_assignment_op!($op, @if {
+            $crate::_closure_args_internal!(@assignment $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: mutref $($params)*] $($tail)*);
+        } {
+            $callback!(@error not_assignment_op $op);
+        });
+    };
+    (@assignment $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: mutref $($params:tt)*] -> $($tail:tt)*) => {
+        $callback!(@error shape $op [$($impl_attrs)*] $(#[$attrs])* |[$($lhs_i)*]: mutref);
+    };
+    (@assignment $callback:path, [$($head:tt)*] $($tail:tt)*) => {
+        $callback!($($head)* | $($tail)*);
+    };
+    (@check $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: own $lhs:tt] $($tail:tt)*) => {
+        $crate::_parse_unary_op!($op, @if {
+            $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: own $lhs | $($tail)*);
+        } {
+            $callback!(@error not_unary_op $op);
+        });
+    };
+    (@check $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt] $($tail:tt)*) => {
+        $crate::_parse_unary_op!($op, @if {
+            $callback!($op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs | $($tail)*);
+        } {
+            $callback!(@error not_unary_op $op);
+        });
+    };
+    // Comparison operators take both operands by reference
+    (@check $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | [$($lhs_i:tt)*]: ref $lhs_lt:tt $lhs:tt, [$($rhs_i:tt)*]: ref $rhs_lt:tt $rhs:tt] $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@binary $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | [$($lhs_i)*]: ref $lhs_lt $lhs, [$($rhs_i)*]: ref $rhs_lt $rhs] $($tail)*);
+    };
+    (@check $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($params:tt)*] $($tail:tt)*) => {
+        $crate::_parse_comparison_op!($op, @if {
+            $callback!(@error borrowed_comparison $op);
+        } {
+            $crate::_closure_args_internal!(@binary $callback, [$op [$($impl_attrs)*] $(#[$attrs])* | $($params)*] $($tail)*);
+        });
+    };
+    (@binary $callback:path, [$op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* | $($params:tt)*] $($tail:tt)*) => {
+        $crate::_parse_binary_op!($op, @if {
+            $callback!($op [$($impl_attrs)*] $(#[$attrs])* | $($params)* | $($tail)*);
+        } {
+            $callback!(@error not_binary_op $op);
+        });
+    };
+
+    // Type lists expand into one set of impls per type, each of which goes through the rules below.
+    // They have to be matched first, since `&` followed by a `{...}` group is a hard error for `ty`
+    ($callback:path, $head:tt $pat:tt : {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : own {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [own] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : ref $lt:lifetime {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [ref $lt] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : ref {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [ref] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : mutref {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [mutref] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : &mut {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [&mut] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : &$lt:lifetime {$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [&$lt] [] [$($types)+] [$($tail)*]);
+    };
+    ($callback:path, $head:tt $pat:tt : &{$($types:tt)+} $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat [&] [] [$($types)+] [$($tail)*]);
+    };
+    // Each type can have an `&`, `&'a` or `&mut` of its own, which is split off before the rest is
+    // parsed as a `ty`, the same way as for a single type
+    (@types $callback:path, $head:tt $pat:tt $prefix:tt [$($done:tt)*] [&mut $t:ty $(, $($types:tt)*)?] $tail:tt) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat $prefix [$($done)* [&mut $t]] [$($($types)*)?] $tail);
+    };
+    (@types $callback:path, $head:tt $pat:tt $prefix:tt [$($done:tt)*] [&$lt:lifetime $t:ty $(, $($types:tt)*)?] $tail:tt) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat $prefix [$($done)* [&$lt $t]] [$($($types)*)?] $tail);
+    };
+    (@types $callback:path, $head:tt $pat:tt $prefix:tt [$($done:tt)*] [&$t:ty $(, $($types:tt)*)?] $tail:tt) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat $prefix [$($done)* [&$t]] [$($($types)*)?] $tail);
+    };
+    (@types $callback:path, $head:tt $pat:tt $prefix:tt [$($done:tt)*] [$t:ty $(, $($types:tt)*)?] $tail:tt) => {
+        $crate::_closure_args_internal!(@types $callback, $head $pat $prefix [$($done)* [$t]] [$($($types)*)?] $tail);
+    };
+    (@types $callback:path, $head:tt $pat:tt [$($prefix:tt)*] [[$($t:tt)*] $($done:tt)*] [] [$($tail:tt)*]) => {
+        $crate::_closure_args_internal!($callback, $head $pat : $($prefix)* $($t)* $($tail)*);
+        $crate::_closure_args_internal!(@types $callback, $head $pat [$($prefix)*] [$($done)*] [] [$($tail)*]);
+    };
+    (@types $callback:path, $head:tt $pat:tt $prefix:tt [] [] $tail:tt) => {};
+
+    // Explicit ownership keywords
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : own $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: own $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : own $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: own $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : ref $lt:lifetime $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: ref [$lt] $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : ref $lt:lifetime $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: ref [$lt] $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : ref $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: ref [] $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : ref $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: ref [] $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : mutref $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: mutref $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : mutref $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: mutref $t] $($tail)*);
+    };
+
+    // Regular types
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &mut $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: mutref $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &mut $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: mutref $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &$lt:lifetime $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: ref [$lt] $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &$lt:lifetime $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: ref [$lt] $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &$t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: ref [] $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : &$t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: ref [] $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : $t:ty, $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* [$($pat)*]: own $t,] [] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] : $t:ty | $($tail:tt)*) => {
+        $crate::_closure_args_internal!(@done $callback, [$($head)* [$($pat)*]: own $t] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($pat:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)*] [$($pat)* $next] $($tail)*);
+    };
+    // Ran out of tokens while looking for the `:` after a pattern
+    ($callback:path, [$op:tt $($head:tt)*] [$($pat:tt)*]) => {
+        $callback!(@error untyped_args $op);
+    };
+}
+
+/// Splits leading generic params off of the tokens by counting angle brackets, for the places
+/// where the end of the generic params can't be found by looking for a `|`. Invokes
+/// `$callback!($($args)* [generic params] rest...)`, with an empty group if there are none.
+/// `@inner` is used when the opening `<` has already been matched, and leaves out the brackets.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _split_generic_params_internal {
+    (@count $callback:path, $args:tt [$($depth:tt)*] [$($generic_params:tt)*] < $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [@ $($depth)*] [$($generic_params)* <] $($tail)*);
+    };
+    (@count $callback:path, $args:tt [$($depth:tt)*] [$($generic_params:tt)*] << $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [@ @ $($depth)*] [$($generic_params)* <<] $($tail)*);
+    };
+    (@count $callback:path, [$($args:tt)*] [@] [$($generic_params:tt)*] > $($tail:tt)*) => {
+        $callback!($($args)* [$($generic_params)* >] $($tail)*);
+    };
+    (@count $callback:path, $args:tt [@ $($depth:tt)*] [$($generic_params:tt)*] > $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [$($depth)*] [$($generic_params)* >] $($tail)*);
+    };
+    (@count $callback:path, [$($args:tt)*] [@ @] [$($generic_params:tt)*] >> $($tail:tt)*) => {
+        $callback!($($args)* [$($generic_params)* >>] $($tail)*);
+    };
+    (@count $callback:path, $args:tt [@ @ $($depth:tt)*] [$($generic_params:tt)*] >> $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [$($depth)*] [$($generic_params)* >>] $($tail)*);
+    };
+    // In `@inner` mode the list is closed by `>` at the bottom of the stack (`^`), which is dropped
+    (@count $callback:path, [$($args:tt)*] [^] [$($generic_params:tt)*] > $($tail:tt)*) => {
+        $callback!($($args)* [$($generic_params)*] $($tail)*);
+    };
+    (@count $callback:path, [$($args:tt)*] [@ ^] [$($generic_params:tt)*] >> $($tail:tt)*) => {
+        $callback!($($args)* [$($generic_params)* >] $($tail)*);
+    };
+    (@count $callback:path, $args:tt [$($depth:tt)*] [$($generic_params:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [$($depth)*] [$($generic_params)* $next] $($tail)*);
+    };
+    // Unclosed generic params
+    (@count $callback:path, [$($args:tt)*] [$($depth:tt)*] [$($generic_params:tt)*]) => {
+        $callback!($($args)* [$($generic_params)*]);
+    };
+
+    // Only the params between the angle brackets, with the opening `<` already taken off
+    (@inner $callback:path, $args:tt $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [^] [] $($tail)*);
+    };
+    ($callback:path, $args:tt < $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!(@count $callback, $args [] [] < $($tail)*);
+    };
+    ($callback:path, [$($args:tt)*] $($tail:tt)*) => {
+        $callback!($($args)* [] $($tail)*);
+    };
+}
+
+/// Expands the path delegation form, `LHS, RHS => OUT = path::to::fn`, into the equivalent
+/// closure `|a: LHS, b: RHS| -> OUT { path::to::fn(a, b) }` and hands it to
+/// `_closure_args_internal`. `_generic_params_shifter_internal` calls this with the tokens it
+/// collected once it runs out of tokens without finding a closure, generic params included, so the
+/// fn form with generic params ends up here as well and is passed on once they're split off.
+///
+/// Each type is parsed as a `ty` (after splitting off any `&`/`&mut`), so the types are passed on
+/// with the explicit ownership keywords.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _delegate_internal {
+    // Generic params can't be parsed as a `ty`, so they're split off first. `impl_ops!` puts the
+    // where clause of the block in front of them
+    (@split $callback:path, $names:tt [$($head:tt)*] [$($where_clause:tt)*] [$($generic_params:tt)*] fn ($($args:tt)*) $($tail:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($args)* | $($tail)* $($where_clause)* $($generic_params)*);
+    };
+    (@split $callback:path, $names:tt $head:tt [$($where_clause:tt)*] [$($generic_params:tt)*] $($tail:tt)*) => {
+        $crate::_delegate_internal!(@lhs $callback, $names $head [$($where_clause)* $($generic_params)*] $($tail)*);
+    };
+
+    // The argument types, written the same way as in a closure
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &mut $lhs:ty, $($tail:tt)*) => {
+        $crate::_delegate_internal!(@rhs $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: mutref $lhs,] $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &mut $lhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: mutref $lhs] [$a] => $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &mut $lhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: mutref $lhs] [$a] = $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs_lt:lifetime $lhs:ty, $($tail:tt)*) => {
+        $crate::_delegate_internal!(@rhs $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs_lt $lhs,] $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs_lt:lifetime $lhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs_lt $lhs] [$a] => $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs_lt:lifetime $lhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs_lt $lhs] [$a] = $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs:ty, $($tail:tt)*) => {
+        $crate::_delegate_internal!(@rhs $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs,] $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs] [$a] => $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] &$lhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: ref $lhs] [$a] = $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] $lhs:ty, $($tail:tt)*) => {
+        $crate::_delegate_internal!(@rhs $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: own $lhs,] $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] $lhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: own $lhs] [$a] => $($tail)*);
+    };
+    (@lhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] $lhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$a: own $lhs] [$a] = $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &mut $rhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: mutref $rhs] [$a, $b] => $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &mut $rhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: mutref $rhs] [$a, $b] = $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &$rhs_lt:lifetime $rhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: ref $rhs_lt $rhs] [$a, $b] => $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &$rhs_lt:lifetime $rhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: ref $rhs_lt $rhs] [$a, $b] = $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &$rhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: ref $rhs] [$a, $b] => $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] &$rhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: ref $rhs] [$a, $b] = $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] $rhs:ty => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: own $rhs] [$a, $b] => $($tail)*);
+    };
+    (@rhs $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] $rhs:ty = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@out $callback, [$a $b] [$($head)*] [$($generic_params)*] [$($params)* $b: own $rhs] [$a, $b] = $($tail)*);
+    };
+
+    // The output type (which can be left out like in a closure) and the path to delegate to
+    (@out $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] [$($args:tt)*] => $out:ty = $fn_path:path) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($params)* | -> $out { $fn_path($($args)*) } $($generic_params)*);
+    };
+    (@out $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] [$($args:tt)*] => $out:ty = $fn_path:path where $($where_clause:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($params)* | -> $out where $($where_clause)* { $fn_path($($args)*) } $($generic_params)*);
+    };
+    (@out $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] [$($args:tt)*] = $fn_path:path) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($params)* | { $fn_path($($args)*) } $($generic_params)*);
+    };
+    (@out $callback:path, [$a:ident $b:ident] [$($head:tt)*] [$($generic_params:tt)*] [$($params:tt)*] [$($args:tt)*] = $fn_path:path where $($where_clause:tt)*) => {
+        $crate::_closure_args_internal!($callback, [$($head)* |] [] $($params)* | where $($where_clause)* { $fn_path($($args)*) } $($generic_params)*);
+    };
+
+    // Anything else isn't any of the supported forms
+    (@$state:ident $callback:path, [$a:ident $b:ident] [$op:tt $($head:tt)*] $($tail:tt)*) => {
+        $callback!(@error no_closure $op);
+    };
+
+    // An array type as the lhs
+    ($callback:path, [$($head:tt)*] [$($lhs:tt)*] , $($tail:tt)*) => {
+        $crate::_delegate_internal!(@lhs $callback, [a b] [$($head)*] [] [$($lhs)*], $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($lhs:tt)*] => $($tail:tt)*) => {
+        $crate::_delegate_internal!(@lhs $callback, [a b] [$($head)*] [] [$($lhs)*] => $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] [$($lhs:tt)*] = $($tail:tt)*) => {
+        $crate::_delegate_internal!(@lhs $callback, [a b] [$($head)*] [] [$($lhs)*] = $($tail)*);
+    };
+    // The where clause of an `impl_ops!` block
+    ($callback:path, [$($head:tt)*] [$($where_clause:tt)*] $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!($crate::_delegate_internal, [@split $callback, [a b] [$($head)*] [[$($where_clause)*]]] $($tail)*);
+    };
+    ($callback:path, [$($head:tt)*] $($tail:tt)*) => {
+        $crate::_split_generic_params_internal!($crate::_delegate_internal, [@split $callback, [a b] [$($head)*] []] $($tail)*);
+    };
+}
+
+/// Emits the given items only if none of the given lifetimes are named. `impl_op_ex!` and friends
+/// use this to skip the owned variants of an operand whose lifetime was named in the closure, as
+/// the lifetime may be used by the output type and can't be satisfied by a local borrow.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _unless_named_lifetimes_internal {
+    ([] $($items:tt)*) => {
+        $($items)*
+    };
+    ([$($lt:lifetime)+] $($items:tt)*) => {};
+}
+
+/// Expands `[op1, op2, ...] rest...` into `$callback!($($args)* op rest...)` for each listed
+/// operator, replacing the `$op` placeholder in the body with the operator.
+///
+/// A transcriber can't write a `$` itself, and `$op` wouldn't refer to a metavariable it declared
+/// anyway because of hygiene. So the body is searched for the placeholder first (descending into
+/// groups by pushing what's left of the outer group onto a stack), and its own `$` and `op`
+/// tokens are used to declare the `$op:tt` metavariable of a local macro that does the replacing.
+/// Every punctuation token other than `$` (and `_`, which isn't an `ident`) is ruled out by hand,
+/// since `$` can't be matched literally either. Without a placeholder the operators are simply expanded one at a time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _op_list_internal {
+    // The placeholder is looked for starting from the first `{...}` group (usually the body). If
+    // that's a type list instead, the search carries on after it
+    (@body $list:tt $done:tt {$($group:tt)*} $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list [[$($tail)*]]] [op] [] [] $($group)*);
+    };
+    (@body $list:tt $done:tt $group:tt $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@body $list] [] [] [] $($tail)*);
+    };
+    (@body [$callback:path, $args:tt $ops:tt $tokens:tt] $done:tt) => {
+        $crate::_op_list_internal!(@each $callback, $args $ops $tokens);
+    };
+
+    // Then the search stops at groups and at each identifier that could be the placeholder, along
+    // with the token before it. The metavariable has to be declared with the identifier from the
+    // body, as one from the crate wouldn't match the `$op` in the body
+    (@find $list:tt [$($stack:tt)*] $done:tt ($($group:tt)*) $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list [[$($tail)*] $($stack)*]] [op] [] [] $($group)*);
+    };
+    (@find $list:tt [$($stack:tt)*] $done:tt [$($group:tt)*] $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list [[$($tail)*] $($stack)*]] [op] [] [] $($group)*);
+    };
+    (@find $list:tt [$($stack:tt)*] $done:tt {$($group:tt)*} $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list [[$($tail)*] $($stack)*]] [op] [] [] $($group)*);
+    };
+    (@find $list:tt $stack:tt $done:tt $dollar:tt op $placeholder:ident $($tail:tt)*) => {
+        $crate::_op_list_internal!(@dollar $dollar [$list $stack $placeholder $($tail)*]);
+    };
+    (@find $list:tt $stack:tt $done:tt $before:tt $other:ident $placeholder:ident $($tail:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list $stack] [op] [] [] $placeholder $($tail)*);
+    };
+    (@find $list:tt [[$($outer:tt)*] $($stack:tt)*] $done:tt) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list [$($stack)*]] [op] [] [] $($outer)*);
+    };
+    (@find [$callback:path, $args:tt $ops:tt $tokens:tt] [] $done:tt) => {
+        $crate::_op_list_internal!(@each $callback, $args $ops $tokens);
+    };
+
+    (@dollar $not_dollar:ident [$list:tt $stack:tt $($tail:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list $stack] [op] [] [] $($tail)*);
+    };
+    (@dollar $not_dollar:literal [$list:tt $stack:tt $($tail:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list $stack] [op] [] [] $($tail)*);
+    };
+    (@dollar $not_dollar:lifetime [$list:tt $stack:tt $($tail:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list $stack] [op] [] [] $($tail)*);
+    };
+    (@dollar _ $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar = $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar == $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar != $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar < $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar <= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar > $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar >= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar && $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar || $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ! $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ~ $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar + $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar - $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar * $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar / $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar % $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ^ $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar & $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar | $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar << $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar >> $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar += $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar -= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar *= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar /= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar %= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ^= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar &= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar |= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar <<= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar >>= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar @ $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar . $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar .. $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ... $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ..= $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar , $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ; $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar : $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar :: $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar -> $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar => $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar # $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    (@dollar ? $k:tt) => ($crate::_op_list_internal!(@skip $k););
+    // Nothing else is left, so this is the `$op` placeholder
+    (@dollar $dollar:tt [[$callback:path, [$($args:tt)*] [$($ops:tt),+] [$($tokens:tt)*]] $stack:tt $placeholder:ident $($tail:tt)*]) => {
+        macro_rules! __auto_ops_op_list {
+            ($dollar $placeholder:tt) => {
+                $callback!($($args)* $dollar $placeholder $($tokens)*);
+            };
+        }
+        $(__auto_ops_op_list!($ops);)+
+    };
+    (@skip [$list:tt $stack:tt $($tail:tt)*]) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@find $list $stack] [op] [] [] $($tail)*);
+    };
+
+    (@each $callback:path, [$($args:tt)*] [$op:tt $(, $ops:tt)*] [$($tokens:tt)*]) => {
+        $callback!($($args)* $op $($tokens)*);
+        $crate::_op_list_internal!(@each $callback, [$($args)*] [$($ops),*] [$($tokens)*]);
+    };
+    (@each $callback:path, $args:tt [] $tokens:tt) => {};
+
+    ($callback:path, $args:tt $ops:tt , $($tokens:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@body [$callback, $args $ops [$($tokens)*]]] [] [] [] $($tokens)*);
+    };
+    ($callback:path, $args:tt $ops:tt $($tokens:tt)*) => {
+        $crate::_find_marker_internal!($crate::_op_list_internal, [@body [$callback, $args $ops [$($tokens)*]]] [] [] [] $($tokens)*);
+    };
+}
+
+/// Parses the `trait path::to::Trait::method [type Assoc]` form of the operator into a single
+/// `(trait [path::to::Trait] method Assoc)` group, which the rest of the macros can carry around as
+/// `$op:tt` and `_parse_binary_op` unpacks. The tokens are munched into the path until we find an
+/// identifier that isn't followed by `::`, which is the method. The associated type defaults to
+/// `Output`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _trait_op_internal {
+    ([$($shifter:tt)*] [$($path:tt)*] :: $segment:ident :: $($tail:tt)*) => {
+        $crate::_trait_op_internal!([$($shifter)*] [$($path)* :: $segment] :: $($tail)*);
+    };
+    ([$($shifter:tt)*] [$($path:tt)*] :: $ops_fn:ident type $ops_output:ident , $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!($($shifter)*, (trait [$($path)*] $ops_fn $ops_output) $($tail)*);
+    };
+    ([$($shifter:tt)*] [$($path:tt)*] :: $ops_fn:ident type $ops_output:ident $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!($($shifter)*, (trait [$($path)*] $ops_fn $ops_output) $($tail)*);
+    };
+    ([$($shifter:tt)*] [$($path:tt)*] :: $ops_fn:ident , $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!($($shifter)*, (trait [$($path)*] $ops_fn Output) $($tail)*);
+    };
+    ([$($shifter:tt)*] [$($path:tt)*] :: $ops_fn:ident $($tail:tt)*) => {
+        $crate::_generic_params_shifter_internal!($($shifter)*, (trait [$($path)*] $ops_fn Output) $($tail)*);
+    };
+    ([$($shifter:tt)*] [$($path:tt)*] $next:tt $($tail:tt)*) => {
+        $crate::_trait_op_internal!([$($shifter)*] [$($path)* $next] $($tail)*);
+    };
+}
+
+/// Reports misuse of the public macros with a `compile_error!` naming the macro (`$mac`), the
+/// operator and the rule that was broken. The `_impl_op_*_internal` macros forward their
+/// `@error <kind> $op ...` arms here.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_op_error_internal {
+    ($mac:ident, no_closure $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "expected a closure such as `|a: LHS, b: RHS| -> OUT {...}`, `fn(a: LHS, b: RHS) -> OUT {...}` or `LHS, RHS => OUT = path::to::fn` after the operator and any generic params");
+    };
+    ($mac:ident, untyped_args $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "every closure argument needs a type, e.g. `|a: LHS, b: &RHS|`");
+    };
+    ($mac:ident, borrowed_output $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "a borrowed output type must name a lifetime from the generic params that one of the arguments uses, e.g. `<'a> |a: &'a LHS, b: RHS| -> &'a OUT`");
+    };
+    ($mac:ident, not_binary_op $op:tt $($tail:tt)*) => {
+        $crate::_parse_assignment_op!($op, @if {
+            $crate::_impl_op_error_internal!(@emit $mac, $op,
+                "assignment operators must take `&mut LHS` as their first argument, e.g. `|a: &mut LHS, b: RHS| {...}`");
+        } {
+            $crate::_impl_op_error_internal!(@emit $mac, $op,
+                "not a binary operator (expected one of `+`, `-`, `*`, `/`, `%`, `&`, `|`, `^`, `<<`, `>>`, `==`, `cmp`, their trait names, or `trait path::to::Trait::method`)");
+        });
+    };
+    ($mac:ident, not_unary_op (trait $($op:tt)*) $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, (trait $($op)*),
+            "user-defined traits are implemented as binary operators and take two arguments");
+    };
+    ($mac:ident, not_unary_op $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "not a unary operator (expected `!`, `-`, `*`, `Not`, `Neg` or `Deref`)");
+    };
+    ($mac:ident, not_assignment_op $op:tt $($tail:tt)*) => {
+        $crate::_parse_binary_op!($op, @if {
+            $crate::_impl_op_error_internal!(@emit $mac, $op,
+                "only assignment operators can take a `&mut` argument");
+        } {
+            $crate::_impl_op_error_internal!(@emit $mac, $op,
+                "not an assignment operator (expected one of `+=`, `-=`, `*=`, `/=`, `%=`, `&=`, `|=`, `^=`, `<<=`, `>>=` or their trait names)");
+        });
+    };
+    ($mac:ident, not_commutative $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "only binary operators other than `[]` can be implemented commutatively");
+    };
+    ($mac:ident, not_reusable $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "`reuse` needs a binary operator with a matching assignment operator (e.g. `+` and `+=`), and can't be used with unary or assignment operators");
+    };
+    ($mac:ident, borrowed_comparison $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "comparison operators take both operands by reference, e.g. `|a: &LHS, b: &RHS| -> bool {...}`");
+    };
+    ($mac:ident, unsupported_mode $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "comparison, index and deref operators can't be used with `mut` or `ptr`");
+    };
+    ($mac:ident, not_foldable $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "`fold` needs `+` or `*` taking `&T` twice and returning `T`, without named lifetimes, e.g. `|a: &T, b: &T| -> T {...}`");
+    };
+    ($mac:ident, deref_shape $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "dereferencing takes `&LHS` and returns `&TARGET`, or takes `&mut LHS` and returns `&mut TARGET`, e.g. `|a: &LHS| -> &TARGET {...}`");
+    };
+    ($mac:ident, index_shape $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "index operators take `&LHS` and return `&OUT`, or take `&mut LHS` and return `&mut OUT`, e.g. `|a: &LHS, i: IDX| -> &OUT {...}`");
+    };
+    ($mac:ident, matrix_closure $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "expected a closure with untyped arguments such as `|a, b| -> OUT {...}` after the type lists, since the argument types come from the lists");
+    };
+
+    // The operator was fine, but the closure didn't match any of the supported forms
+    ($mac:ident, shape $op:tt [$($impl_attrs:tt)*] $(#[$attrs:meta])* |[$($lhs_i:tt)*]: mutref $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "assignment operators take two arguments and have no output type, e.g. `|a: &mut LHS, b: RHS| {...}`");
+    };
+    ($mac:ident, shape $op:tt $($tail:tt)*) => {
+        $crate::_impl_op_error_internal!(@emit $mac, $op,
+            "expected a closure taking one argument (unary operators) or two arguments (binary and assignment operators), where only the first argument of an assignment operator can be `&mut`");
+    };
+
+    (@emit $mac:ident, (trait [$($ops_trait:tt)*] $ops_fn:ident $ops_output:ident), $msg:literal) => {
+        compile_error!(concat!(
+            "`", stringify!($mac), "!(trait ", $(stringify!($ops_trait),)* "::", stringify!($ops_fn), " ...)`: ", $msg
+        ));
     };
-    // Until then, shift tokens describing the generic parameters to the end
-    ($callback:path, $op:tt $(#[$attrs:meta])* $next:tt $($tail:tt)*) => {
-        $crate::_generic_params_shifter_internal!($callback, $op $(#[$attrs])* $($tail)* $next);
+    (@emit $mac:ident, $op:tt, $msg:literal) => {
+        compile_error!(concat!("`", stringify!($mac), "!(", stringify!($op), " ...)`: ", $msg));
     };
 }
@@ -1,15 +1,34 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _parse_unary_op {
+    // Operator tokens
     (-, $($t:tt)+) => ($crate::_impl_unary_op_internal!(Neg, neg, $($t)+););
     (!, $($t:tt)+) => ($crate::_impl_unary_op_internal!(Not, not, $($t)+););
+    (*, $($t:tt)+) => ($crate::_impl_deref_op_internal!($($t)+););
+
+    // Trait names
+    (Neg, $($t:tt)+) => ($crate::_impl_unary_op_internal!(Neg, neg, $($t)+););
+    (Not, $($t:tt)+) => ($crate::_impl_unary_op_internal!(Not, not, $($t)+););
+    (Deref, $($t:tt)+) => ($crate::_impl_deref_op_internal!($($t)+););
+
+    // Anything else isn't an operator of this kind
+    ($op:tt, @if $then:tt {$($else:tt)*}) => ($($else)*);
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _impl_unary_op_internal {
-    ($ops_trait:ident, $ops_fn:ident, &$lhs:ty, $out:ty, $lhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait for &$lhs {
+    // `_parse_unary_op!($op, @if {...} {...})` checks whether `$op` is a unary operator
+    ($ops_trait:ident, $ops_fn:ident, @if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    // `_parse_unary_op!($op, @call out = (lhs));` binds the result of the operator to `out`
+    ($ops_trait:ident, $ops_fn:ident, @call $out:ident = ($($args:tt)*)) => {
+        let $out = ::core::ops::$ops_trait::$ops_fn($($args)*);
+    };
+    ($ops_trait:ident, $ops_fn:ident, ref [$($lhs_lt:lifetime)?] $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait for &$($lhs_lt)? $lhs where $($where_clause)* {
             type Output = $out;
 
             $(#[$attrs])*
@@ -19,8 +38,9 @@ macro_rules! _impl_unary_op_internal {
             }
         }
     };
-    ($ops_trait:ident, $ops_fn:ident, $lhs:ty, $out:ty, $lhs_i:ident, $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
-        impl$($generic_params)* ::core::ops::$ops_trait for $lhs {
+    ($ops_trait:ident, $ops_fn:ident, own $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::$ops_trait for $lhs where $($where_clause)* {
             type Output = $out;
 
             $(#[$attrs])*
@@ -30,4 +50,56 @@ macro_rules! _impl_unary_op_internal {
             }
         }
     };
+    ($ops_trait:ident, $ops_fn:ident, ref [$($lhs_lt:lifetime)?] $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_unary_op_internal!(
+            $ops_trait, $ops_fn, ref [$($lhs_lt)?] $lhs, $out, $lhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    ($ops_trait:ident, $ops_fn:ident, own $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_unary_op_internal!(
+            $ops_trait, $ops_fn, own $lhs, $out, $lhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+}
+
+/// `Deref` is implemented for a borrowed LHS and `DerefMut` for a mutably borrowed one, with the
+/// type the body returns a borrow of as the target (`_closure_args_internal` already took off its
+/// `&` or `&mut`). The traits take `self` by reference, so the owned LHS generated by
+/// `impl_op_ex!` is skipped.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _impl_deref_op_internal {
+    // `_parse_unary_op!(*, @if {...} {...})` checks whether `*` is a unary operator
+    (@if {$($then:tt)*} $else:tt) => {
+        $($then)*
+    };
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::Deref for $lhs where $($where_clause)* {
+            type Target = $out;
+
+            $(#[$attrs])*
+            fn deref(&self) -> &Self::Target {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (mutref $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block [$($where_clause:tt)*] $($generic_params:tt)*) => {
+        $(#[$impl_attrs])*
+        impl$($generic_params)* ::core::ops::DerefMut for $lhs where $($where_clause)* {
+            $(#[$attrs])*
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                let $lhs_i = self;
+                $body
+            }
+        }
+    };
+    (own $lhs:ty, $($tail:tt)*) => {};
+    (ref [$($lhs_lt:lifetime)?] $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_deref_op_internal!(
+            ref [$($lhs_lt)?] $lhs, $out, $lhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
+    (mutref $lhs:ty, $out:ty, $lhs_i:ident, [$(#[$impl_attrs:meta])*] $(#[$attrs:meta])* $body:block $($generic_params:tt)*) => {
+        $crate::_impl_deref_op_internal!(
+            mutref $lhs, $out, $lhs_i, [$(#[$impl_attrs])*] $(#[$attrs])* $body [] $($generic_params)*);
+    };
 }
@@ -1,3 +1,6 @@
+// These tests deliberately spell out operations that clippy would simplify
+#![allow(clippy::identity_op, clippy::modulo_one)]
+
 use auto_ops::{impl_op, impl_op_ex};
 
 mod kong {
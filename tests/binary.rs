@@ -1,3 +1,6 @@
+// These tests deliberately spell out operations that clippy would simplify
+#![allow(clippy::identity_op, clippy::let_and_return, clippy::op_ref)]
+
 use auto_ops::{impl_op, impl_op_commutative, impl_op_ex, impl_op_ex_commutative};
 
 mod kong {
@@ -843,3 +846,60 @@ mod impl_op_sequence_types {
         );
     }
 }
+
+// Each copy of a body would get its own `static`, so counting the calls through every variant
+// shows that they all end up in the same one
+mod shared_body {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Donkey;
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Diddy;
+
+    impl_op_ex!(+ |_a: &Donkey, _b: &Donkey| -> usize {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        CALLS.fetch_add(1, Ordering::SeqCst) + 1
+    });
+    #[test]
+    fn impl_op_ex() {
+        assert_eq!(1, Donkey + Donkey);
+        assert_eq!(2, &Donkey + Donkey);
+        assert_eq!(3, Donkey + &Donkey);
+        assert_eq!(4, &Donkey + &Donkey);
+    }
+
+    impl_op_ex_commutative!(+ |_a: &Donkey, _b: &Diddy| -> usize {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        CALLS.fetch_add(1, Ordering::SeqCst) + 1
+    });
+    #[test]
+    fn impl_op_ex_commutative() {
+        assert_eq!(1, Donkey + Diddy);
+        assert_eq!(2, &Donkey + Diddy);
+        assert_eq!(3, Donkey + &Diddy);
+        assert_eq!(4, &Donkey + &Diddy);
+        assert_eq!(5, Diddy + Donkey);
+        assert_eq!(6, &Diddy + Donkey);
+        assert_eq!(7, Diddy + &Donkey);
+        assert_eq!(8, &Diddy + &Donkey);
+    }
+
+    static ASSIGN_CALLS: AtomicUsize = AtomicUsize::new(0);
+    impl_op_ex!(+= |_a: &mut Donkey, _b: &Diddy| {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        ASSIGN_CALLS.store(CALLS.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+    });
+    #[test]
+    fn assignment() {
+        let mut donkey = Donkey;
+        donkey += Diddy;
+        donkey += &Diddy;
+        assert_eq!(2, ASSIGN_CALLS.load(Ordering::SeqCst));
+        let mut borrowed = &mut donkey;
+        borrowed += Diddy;
+        borrowed += &Diddy;
+        assert_eq!(4, ASSIGN_CALLS.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,122 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use core::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug)]
+struct Meters(f64);
+#[derive(Clone, Copy, Debug)]
+struct Feet(f64);
+#[derive(Clone, Copy, Debug)]
+struct Yards(f64);
+#[derive(Clone, Copy, Debug)]
+struct Inches(f64);
+
+impl_op!(== |a: &Meters, b: &Feet| -> bool { a.0 == b.0 * 0.5 });
+#[test]
+fn regular() {
+    assert!(Meters(1.0) == Feet(2.0));
+    assert!(&Meters(1.0) == &Feet(2.0));
+    assert!(Meters(1.0) != Feet(1.0));
+}
+
+impl_op_ex!(== |a: &Meters, b: &Yards| -> bool { a.0 == b.0 * 2.0 });
+#[test]
+fn ex() {
+    assert!(Meters(2.0) == Yards(1.0));
+    assert!(&Meters(2.0) == Yards(1.0));
+    assert!(Meters(2.0) == &Yards(1.0));
+    assert!(&Meters(2.0) == &Yards(1.0));
+    assert!(&Meters(2.0) != Yards(2.0));
+}
+
+impl_op_commutative!(PartialEq |a: &Feet, b: &Yards| { a.0 == b.0 * 4.0 });
+#[test]
+fn commutative() {
+    assert!(Feet(4.0) == Yards(1.0));
+    assert!(Yards(1.0) == Feet(4.0));
+    assert!(&Yards(1.0) == &Feet(4.0));
+    assert!(Yards(1.0) != Feet(1.0));
+}
+
+impl_op_ex_commutative!(== |a: &Inches, b: &Feet| -> bool { a.0 == b.0 * 12.0 });
+#[test]
+fn ex_commutative() {
+    assert!(Inches(12.0) == Feet(1.0));
+    assert!(&Inches(12.0) == Feet(1.0));
+    assert!(Inches(12.0) == &Feet(1.0));
+    assert!(Feet(1.0) == Inches(12.0));
+    assert!(&Feet(1.0) == Inches(12.0));
+    assert!(Feet(1.0) == &Inches(12.0));
+    assert!(&Feet(1.0) != &Inches(1.0));
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Seconds(i64);
+#[derive(Clone, Copy, Debug)]
+struct Minutes(i64);
+#[derive(Clone, Copy, Debug)]
+struct Hours(i64);
+
+impl_op!(cmp |a: &Seconds, b: &Minutes| -> Option<Ordering> { a.0.partial_cmp(&(b.0 * 60)) });
+#[test]
+fn ordering() {
+    assert!(Seconds(59) < Minutes(1));
+    assert!(Seconds(60) <= Minutes(1));
+    assert!(&Seconds(61) > &Minutes(1));
+    assert!(Seconds(60) == Minutes(1));
+    assert_eq!(
+        Seconds(120).partial_cmp(&Minutes(1)),
+        Some(Ordering::Greater)
+    );
+}
+
+impl_op_ex_commutative!(PartialOrd |a: &Minutes, b: &Hours| { a.0.partial_cmp(&(b.0 * 60)) });
+#[test]
+fn ordering_ex_commutative() {
+    assert!(Minutes(59) < Hours(1));
+    assert!(&Minutes(59) < Hours(1));
+    assert!(Minutes(59) < &Hours(1));
+    assert!(Minutes(60) == Hours(1));
+
+    // The reversed impls flip the ordering
+    assert!(Hours(1) > Minutes(59));
+    assert!(&Hours(1) > Minutes(59));
+    assert!(Hours(1) >= &Minutes(60));
+    assert!(Hours(1) == Minutes(60));
+    assert_eq!(Hours(1).partial_cmp(&Minutes(61)), Some(Ordering::Less));
+    assert_eq!(Hours(1).partial_cmp(&Minutes(59)), Some(Ordering::Greater));
+}
+
+mod generics {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug)]
+    struct Wrapper<T>(T);
+
+    // `impl_op_ex!` would implement `PartialEq<T>` for `&Wrapper<T>`, which overlaps with the
+    // standard library's impl for references when `T` is a reference
+    impl_op!(== <T: PartialEq> |a: &Wrapper<T>, b: &T| -> bool { a.0 == *b });
+    impl_ops! {
+        <T> for Wrapper<T>;
+
+        impl_op_ex!(== |a: &Self, b: &Option<T>| where T: PartialEq { b.as_ref() == Some(&a.0) });
+        // `[T]` isn't local, so the reversed impl can't leave `T` uncovered under older compilers
+        impl_op_commutative!(cmp |a: &Self, b: &[u8]| where T: PartialOrd + From<u8> + Copy {
+            core::slice::from_ref(&a.0).partial_cmp(&b.iter().map(|&x| T::from(x)).collect::<Vec<_>>()[..])
+        });
+    }
+
+    #[test]
+    fn generics() {
+        assert!(Wrapper(1) == 1);
+        assert!(&Wrapper('a') == &'a');
+        assert!(Wrapper(1) != 2);
+        assert!(Wrapper(1) == Some(1));
+        assert!(&Wrapper(1) != None);
+        assert!(Wrapper(2) > [1u8, 5][..]);
+        assert!([1u8, 5][..] < Wrapper(2));
+        assert!([2u8][..] == Wrapper(2.0));
+    }
+}
@@ -0,0 +1,147 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use core::ops::Mul;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Vec3 {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl Vec3 {
+    fn new(x: i32, y: i32, z: i32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    fn add_impl(a: &Vec3, b: &Vec3) -> Vec3 {
+        Vec3::new(a.x + b.x, a.y + b.y, a.z + b.z)
+    }
+
+    fn dot_impl(a: &Vec3, b: &Vec3) -> i32 {
+        a.x * b.x + a.y * b.y + a.z * b.z
+    }
+
+    fn scale_impl(a: Vec3, s: i32) -> Vec3 {
+        Vec3::new(a.x * s, a.y * s, a.z * s)
+    }
+
+    fn neg_impl(a: &Vec3) -> Vec3 {
+        Vec3::new(-a.x, -a.y, -a.z)
+    }
+
+    fn add_assign_impl(a: &mut Vec3, b: &Vec3) {
+        *a = Vec3::add_impl(a, b);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Wrapper<T>(T);
+
+fn mul_wrapped<T: Copy + Mul<Output = T>>(a: &Wrapper<T>, b: &Wrapper<T>) -> Wrapper<T> {
+    Wrapper(a.0 * b.0)
+}
+
+mod fn_syntax {
+    use super::*;
+
+    impl_op_ex!(- fn(a: &Vec3, b: &Vec3) -> Vec3 { Vec3::new(a.x - b.x, a.y - b.y, a.z - b.z) });
+    #[test]
+    fn binary() {
+        let (a, b) = (Vec3::new(3, 2, 1), Vec3::new(1, 1, 1));
+        assert_eq!(Vec3::new(2, 1, 0), a - b);
+        assert_eq!(Vec3::new(2, 1, 0), &a - b);
+        assert_eq!(Vec3::new(2, 1, 0), a - &b);
+        assert_eq!(Vec3::new(2, 1, 0), &a - &b);
+    }
+
+    impl_op!(-=, #[inline] fn(a: &mut Vec3, b: i32) { a.x -= b; });
+    #[test]
+    fn assignment() {
+        let mut a = Vec3::new(3, 2, 1);
+        a -= 1;
+        assert_eq!(Vec3::new(2, 2, 1), a);
+    }
+
+    impl_op!(! fn(a: Vec3) { Vec3::new(a.z, a.y, a.x) });
+    #[test]
+    fn unary() {
+        assert_eq!(Vec3::new(3, 2, 1), !Vec3::new(1, 2, 3));
+    }
+
+    impl_op!(/ #[allow(clippy::suspicious_arithmetic_impl)] <T: Copy + Mul<Output = T>> fn(a: Wrapper<T>, b: Wrapper<T>) -> T {
+        a.0 * b.0
+    });
+    #[test]
+    fn generic_params() {
+        assert_eq!(6, Wrapper(2) / Wrapper(3));
+    }
+
+    // A `fn (` inside the generic params doesn't start the fn form
+    impl_op!(+ <F: Copy + Into<fn(i32) -> i32>> |a: Wrapper<F>, b: i32| -> i32 { a.0.into()(b) });
+    impl_op!(- <F: Copy + Into<fn(i32) -> i32>> fn(a: Wrapper<F>, b: i32) -> i32 { -a.0.into()(b) });
+    fn double(x: i32) -> i32 {
+        x * 2
+    }
+    #[test]
+    fn fn_pointer_bounds() {
+        let f: fn(i32) -> i32 = double;
+        assert_eq!(6, Wrapper(f) + 3);
+        assert_eq!(-6, Wrapper(f) - 3);
+    }
+}
+
+mod path {
+    use super::*;
+
+    impl_op_ex!(+ &Vec3, &Vec3 => Vec3 = Vec3::add_impl);
+    #[test]
+    fn binary() {
+        let (a, b) = (Vec3::new(1, 2, 3), Vec3::new(1, 1, 1));
+        assert_eq!(Vec3::new(2, 3, 4), a + b);
+        assert_eq!(Vec3::new(2, 3, 4), &a + b);
+        assert_eq!(Vec3::new(2, 3, 4), a + &b);
+        assert_eq!(Vec3::new(2, 3, 4), &a + &b);
+    }
+
+    impl_op_ex!(+= &mut Vec3, &Vec3 = Vec3::add_assign_impl);
+    #[test]
+    fn assignment() {
+        let mut a = Vec3::new(1, 2, 3);
+        a += Vec3::new(1, 1, 1);
+        a += &Vec3::new(1, 1, 1);
+        assert_eq!(Vec3::new(3, 4, 5), a);
+    }
+
+    impl_op_ex!(- &Vec3 => Vec3 = Vec3::neg_impl);
+    #[test]
+    fn unary() {
+        assert_eq!(Vec3::new(-1, -2, -3), -Vec3::new(1, 2, 3));
+        assert_eq!(Vec3::new(-1, -2, -3), -&Vec3::new(1, 2, 3));
+    }
+
+    impl_op_ex!(* &Vec3, &Vec3 => i32 = Vec3::dot_impl);
+    impl_op_commutative!(*Vec3, i32 = Vec3::scale_impl);
+    #[test]
+    fn omitted_output() {
+        assert_eq!(Vec3::new(2, 4, 6), Vec3::new(1, 2, 3) * 2);
+        assert_eq!(Vec3::new(2, 4, 6), 2 * Vec3::new(1, 2, 3));
+        assert_eq!(14, &Vec3::new(1, 2, 3) * Vec3::new(1, 2, 3));
+    }
+
+    impl_op_ex!(* <T: Copy + Mul<Output = T>> &Wrapper<T>, &Wrapper<T> = mul_wrapped);
+    impl_op_ex!(% <T> &Wrapper<Wrapper<T>>, &Wrapper<Wrapper<T>> => Wrapper<T> = unwrap_mul where T: Copy + Mul<Output = T>);
+    fn unwrap_mul<T: Copy + Mul<Output = T>>(
+        a: &Wrapper<Wrapper<T>>,
+        b: &Wrapper<Wrapper<T>>,
+    ) -> Wrapper<T> {
+        mul_wrapped(&a.0, &b.0)
+    }
+    #[test]
+    fn generic_params() {
+        assert_eq!(Wrapper(6), Wrapper(2) * &Wrapper(3));
+        assert_eq!(Wrapper(6), &Wrapper(Wrapper(2)) % Wrapper(Wrapper(3)));
+    }
+}
@@ -0,0 +1,57 @@
+use auto_ops::*;
+
+#[derive(Debug)]
+struct Meters(f64);
+
+impl_op!(*|a: &Meters| -> &f64 { &a.0 });
+impl_op!(*|a: &mut Meters| -> &mut f64 { &mut a.0 });
+#[test]
+fn regular() {
+    let mut m = Meters(1.5);
+    assert_eq!(1.5, *m);
+    *m += 1.0;
+    assert_eq!(2.5, *m);
+    assert_eq!(2.0, m.floor());
+}
+
+#[derive(Debug, Default)]
+struct Name(String);
+
+impl_op_ex!(Deref |a: &Name| -> &str { &a.0 });
+#[test]
+fn ex() {
+    let name = Name("Donkey".to_string());
+    assert_eq!("Donkey", &*name);
+    assert_eq!(6, name.len());
+}
+
+#[derive(Debug)]
+struct Logged(i32);
+
+impl_op!(* #[inline] impl #[allow(unused)] |a: &Logged| -> &i32 { &a.0 });
+#[test]
+fn attributes() {
+    assert_eq!(3, *Logged(3));
+}
+
+mod generics {
+    use super::*;
+
+    struct Wrapper<T>(Vec<T>);
+
+    impl_op!(* <T> |a: &Wrapper<T>| -> &[T] { &a.0 });
+    impl_ops! {
+        <T> for Wrapper<T>;
+
+        impl_op!(Deref |a: &mut Self| -> &mut [T] where T: Clone { &mut a.0 });
+    }
+
+    #[test]
+    fn generics() {
+        let mut w = Wrapper(vec![1, 2, 3]);
+        assert_eq!([1, 2, 3], *w);
+        w[1] = 5;
+        w.reverse();
+        assert_eq!(&[3, 5, 1], &*w);
+    }
+}
@@ -0,0 +1,52 @@
+use auto_ops::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Meters(f64);
+
+impl_op_ex!(+ fold(Meters(0.0)) |a: &Meters, b: &Meters| -> Meters { Meters(a.0 + b.0) });
+#[test]
+fn sum() {
+    let lengths = [Meters(1.5), Meters(2.0), Meters(0.5)];
+    assert_eq!(Meters(4.0), lengths.iter().sum());
+    assert_eq!(Meters(4.0), lengths.iter().cloned().sum());
+    assert_eq!(Meters(0.0), [].iter().sum());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Scale(i32);
+
+impl_op_ex!(Mul fold(Scale(1)) impl #[allow(unused)] |a: &Scale, b: &Scale| -> Self { Scale(a.0 * b.0) });
+#[test]
+fn product() {
+    let scales = vec![Scale(2), Scale(3), Scale(4)];
+    assert_eq!(Scale(24), scales.iter().product());
+    assert_eq!(Scale(24), scales.into_iter().product());
+    assert_eq!(Scale(1), Vec::<Scale>::new().into_iter().product());
+}
+
+mod generics {
+    use super::*;
+    use core::ops::{Add, Mul};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Wrapper<T>(T);
+
+    impl_op_ex!(+ fold(Wrapper(T::default())) <T: Copy + Default> |a: &Wrapper<T>, b: &Wrapper<T>| -> Wrapper<T> where T: Add<Output = T> {
+        Wrapper(a.0 + b.0)
+    });
+    impl_ops! {
+        <T> for Wrapper<T> where T: Copy;
+
+        impl_op_ex!(* fold(Wrapper(T::from(1))) |a: &Self, b: &Self| -> Self where T: Mul<Output = T> + From<u8> {
+            Wrapper(a.0 * b.0)
+        });
+    }
+
+    #[test]
+    fn generics() {
+        let wrappers = vec![Wrapper(1.5), Wrapper(2.0)];
+        assert_eq!(Wrapper(3.5), wrappers.iter().sum());
+        assert_eq!(Wrapper(3.0), wrappers.iter().product());
+        assert_eq!(Wrapper(3.5), wrappers.into_iter().sum());
+    }
+}
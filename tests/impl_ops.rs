@@ -0,0 +1,127 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use core::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Vec2<T> {
+    x: T,
+    y: T,
+}
+
+impl<T> Vec2<T> {
+    fn new(x: T, y: T) -> Vec2<T> {
+        Vec2 { x, y }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Meters(i32);
+
+impl Meters {
+    fn sub_impl(a: &Meters, b: &Meters) -> Meters {
+        Meters(a.0 - b.0)
+    }
+}
+
+trait Dot<Rhs = Self> {
+    type Output;
+    fn dot(self, rhs: Rhs) -> Self::Output;
+}
+
+impl_ops! {
+    #[inline] <T: Copy> for Vec2<T> where T: Add<Output = T>,;
+
+    impl_op_ex!(+ |a: &Self, b: &Self| -> Self { Vec2::new(a.x + b.x, a.y + b.y) });
+    impl_op!(+= |a: &mut Self, b: Self| { *a = *a + b; });
+    impl_op_ex!(- |a: &Self| -> Self where T: Neg<Output = T> { Vec2::new(-a.x, -a.y) });
+    impl_op_ex_commutative!(* |a: &Self, b: Meters| -> Self where T: Mul<Output = T> + From<i32> {
+        Vec2::new(a.x * T::from(b.0), a.y * T::from(b.0))
+    });
+    impl_op_ex!(trait Dot::dot |a: &Self, b: &Self| -> T where T: Mul<Output = T> {
+        a.x * b.x + a.y * b.y
+    });
+    impl_op!(* <U: Into<T>> |a: Self, b: Vec2<U>| -> Self where T: Mul<Output = T> {
+        Vec2::new(a.x * b.x.into(), a.y * b.y.into())
+    });
+}
+
+#[test]
+fn shared_generic_params() {
+    let (a, b) = (Vec2::new(1, 2), Vec2::new(3, 4));
+    assert_eq!(Vec2::new(4, 6), a + b);
+    assert_eq!(Vec2::new(4, 6), &a + &b);
+    assert_eq!(
+        Vec2::new(4.5, 6.5),
+        Vec2::new(1.5, 2.5) + Vec2::new(3.0, 4.0)
+    );
+
+    let mut c = a;
+    c += b;
+    assert_eq!(Vec2::new(4, 6), c);
+}
+
+#[test]
+fn shared_where_clause() {
+    let a = Vec2::new(1, 2);
+    assert_eq!(Vec2::new(-1, -2), -a);
+    assert_eq!(Vec2::new(-1, -2), -&a);
+    assert_eq!(Vec2::new(3, 6), a * Meters(3));
+    assert_eq!(Vec2::new(3, 6), Meters(3) * &a);
+    assert_eq!(11, a.dot(Vec2::new(3, 4)));
+}
+
+#[test]
+fn entry_generic_params() {
+    assert_eq!(Vec2::new(3, 8), Vec2::new(1, 2) * Vec2::new(3u8, 4u8));
+    assert_eq!(
+        Vec2::new(3.0, 8.0),
+        Vec2::new(1.0, 2.0) * Vec2::new(3.0f32, 4.0f32)
+    );
+}
+
+impl_ops! {
+    impl #[cfg(all())] for Meters;
+
+    impl_op_ex!(- &Self, &Self => Self = Meters::sub_impl);
+    impl_op!(! fn(a: Self) -> Self { Meters(-a.0) });
+    impl_op!(% <T: Into<i32>> |a: Self, b: T| -> Self { Meters(a.0 % b.into()) });
+}
+
+#[test]
+fn scope() {
+    assert_eq!(Meters(1), Meters(3) - Meters(2));
+    assert_eq!(Meters(1), &Meters(3) - Meters(2));
+    assert_eq!(Meters(-3), !Meters(3));
+    assert_eq!(Meters(1), Meters(7) % 3u8);
+}
+
+impl_ops! {
+    impl_op!(* |a: Meters, b: i32| -> Meters { Meters(a.0 * b) });
+    impl_op!(/, |a: Meters, b: i32| -> Meters { Meters(a.0 / b) });
+}
+
+#[test]
+fn no_header() {
+    assert_eq!(Meters(6), Meters(3) * 2);
+    assert_eq!(Meters(2), Meters(4) / 2);
+}
+
+mod generic_entries {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Wrapper<T>(T);
+
+    impl_ops! {
+        where T: Copy;
+
+        impl_op!(- <T: Sub<Output = T>> |a: Wrapper<T>, b: Wrapper<T>| -> Wrapper<T> { Wrapper(a.0 - b.0) });
+    }
+
+    #[test]
+    fn where_clause_only() {
+        assert_eq!(Wrapper(1), Wrapper(3) - Wrapper(2));
+    }
+}
@@ -0,0 +1,75 @@
+use auto_ops::*;
+use core::ops::Range;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Cell(i32);
+
+#[derive(Debug, Default)]
+struct Grid {
+    width: usize,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            cells: (0..width * height).map(|i| Cell(i as i32)).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Key(usize);
+
+impl_op!([] |a: &Grid, (x, y): (usize, usize)| -> &Cell { &a.cells[y * a.width + x] });
+impl_op!([] |a: &mut Grid, (x, y): (usize, usize)| -> &mut Cell { &mut a.cells[y * a.width + x] });
+impl_op!(Index |a: &Grid, range: Range<usize>| -> &[Cell] { &a.cells[range] });
+#[test]
+fn regular() {
+    let mut grid = Grid::new(2, 2);
+    assert_eq!(Cell(3), grid[(1, 1)]);
+    grid[(0, 1)] = Cell(7);
+    assert_eq!([Cell(7), Cell(3)], grid[2..4]);
+}
+
+impl_op_ex!([] |a: &Grid, key: &Key| -> &Cell { &a.cells[key.0] });
+impl_op_ex!([] |a: &mut Grid, key: &Key| -> &mut Cell { &mut a.cells[key.0] });
+#[test]
+fn ex() {
+    let mut grid = Grid::new(2, 2);
+    assert_eq!(Cell(1), grid[Key(1)]);
+    assert_eq!(Cell(1), grid[&Key(1)]);
+    grid[Key(0)].0 += 5;
+    grid[&Key(0)].0 += 5;
+    assert_eq!(Cell(10), grid[(0, 0)]);
+}
+
+mod generics {
+    use super::*;
+
+    struct Sparse<T> {
+        entries: Vec<(usize, T)>,
+    }
+
+    impl_ops! {
+        <T> for Sparse<T>;
+
+        impl_op_ex!([] |a: &Self, i: &usize| -> &T {
+            &a.entries.iter().find(|(j, _)| j == i).unwrap().1
+        });
+        impl_op_ex!([] |a: &mut Self, i: &usize| -> &mut T {
+            &mut a.entries.iter_mut().find(|(j, _)| j == i).unwrap().1
+        });
+    }
+
+    #[test]
+    fn generics() {
+        let mut sparse = Sparse {
+            entries: vec![(3, 'a'), (10, 'b')],
+        };
+        assert_eq!('b', sparse[10]);
+        sparse[&3] = 'c';
+        assert_eq!('c', sparse[&3]);
+    }
+}
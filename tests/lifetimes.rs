@@ -0,0 +1,129 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+
+#[derive(Debug, PartialEq)]
+struct Buffer<T> {
+    data: Vec<T>,
+}
+
+impl<T> Buffer<T> {
+    fn new(data: Vec<T>) -> Buffer<T> {
+        Buffer { data }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Range {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Offset(usize);
+
+#[derive(Debug, PartialEq)]
+struct View<'a, T> {
+    data: &'a [T],
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Log<'a> {
+    last: Option<&'a str>,
+}
+
+mod borrowed_output {
+    use super::*;
+
+    impl_op!(/ <'a, T> |a: &'a Buffer<T>, r: Range| -> View<'a, T> {
+        View { data: &a.data[r.start..r.end] }
+    });
+    #[test]
+    fn impl_op() {
+        let buffer = Buffer::new(vec![1, 2, 3, 4]);
+        let view = &buffer / Range { start: 1, end: 3 };
+        assert_eq!(View { data: &[2, 3] }, view);
+    }
+
+    impl_op_ex!(% <'a, T> |a: &'a Buffer<T>, r: &Range| -> View<'a, T> {
+        View { data: &a.data[r.start..r.end] }
+    });
+    #[test]
+    fn impl_op_ex() {
+        let buffer = Buffer::new(vec![1, 2, 3, 4]);
+        assert_eq!(View { data: &[1, 2] }, &buffer % Range { start: 0, end: 2 });
+        assert_eq!(
+            View { data: &[1, 2] },
+            &buffer % &Range { start: 0, end: 2 }
+        );
+    }
+
+    impl_op_ex!(- <'a, T> |a: &'a Buffer<T>| -> &'a [T] { &a.data });
+    #[test]
+    fn unary() {
+        let buffer = Buffer::new(vec![1, 2]);
+        assert_eq!(&[1, 2], -&buffer);
+    }
+}
+
+mod rhs_lifetime {
+    use super::*;
+
+    impl_op_ex!(* <'b, T> |o: &Offset, b: &'b Buffer<T>| -> View<'b, T> {
+        View { data: &b.data[o.0..] }
+    });
+    #[test]
+    fn impl_op_ex() {
+        let buffer = Buffer::new(vec![1, 2, 3]);
+        assert_eq!(View { data: &[2, 3] }, &Offset(1) * &buffer);
+        assert_eq!(View { data: &[2, 3] }, Offset(1) * &buffer);
+    }
+
+    impl_op_ex!(+= <'a> |a: &mut Log<'a>, b: &'a str| { a.last = Some(b); });
+    #[test]
+    fn assignment() {
+        let mut log = Log::default();
+        let message = String::from("hello");
+        log += message.as_str();
+        assert_eq!(Some("hello"), log.last);
+    }
+}
+
+mod commutative {
+    use super::*;
+
+    impl_op_commutative!(& <'a, T> |a: &'a Buffer<T>, o: Offset| -> &'a T { &a.data[o.0] });
+    #[test]
+    fn impl_op_commutative() {
+        let buffer = Buffer::new(vec![1, 2, 3]);
+        assert_eq!(&2, &buffer & Offset(1));
+        assert_eq!(&2, Offset(1) & &buffer);
+    }
+
+    impl_op_ex_commutative!(| <'a, T> |a: &'a Buffer<T>, o: &Offset| -> &'a T { &a.data[o.0] });
+    #[test]
+    fn impl_op_ex_commutative() {
+        let buffer = Buffer::new(vec![1, 2, 3]);
+        assert_eq!(&3, &buffer | Offset(2));
+        assert_eq!(&3, &buffer | &Offset(2));
+        assert_eq!(&3, Offset(2) | &buffer);
+        assert_eq!(&3, &Offset(2) | &buffer);
+    }
+}
+
+mod bounds {
+    use super::*;
+
+    impl_op!(^ <'a, T: ::core::fmt::Debug + 'a> |a: &'a Buffer<T>, b: &'a Buffer<T>| -> Vec<&'a T>
+        where T: PartialEq
+    {
+        a.data.iter().filter(|x| !b.data.contains(x)).collect()
+    });
+    #[test]
+    fn generic_params() {
+        let a = Buffer::new(vec![1, 2, 3]);
+        let b = Buffer::new(vec![2]);
+        assert_eq!(vec![&1, &3], &a ^ &b);
+    }
+}
@@ -0,0 +1,84 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Meters(f64);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Feet(f64);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Inches(f64);
+
+trait Length {
+    fn meters(&self) -> f64;
+}
+
+impl Length for Meters {
+    fn meters(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Length for Feet {
+    fn meters(&self) -> f64 {
+        self.0 * 0.25
+    }
+}
+
+impl Length for Inches {
+    fn meters(&self) -> f64 {
+        self.0 * 0.125
+    }
+}
+
+impl_op_matrix!(+ [Meters, Feet, Inches] x [Meters, Feet, Inches] |a, b| -> Meters {
+    Meters(a.meters() + b.meters())
+});
+#[test]
+fn every_pair() {
+    assert_eq!(Meters(2.0), Meters(1.0) + Meters(1.0));
+    assert_eq!(Meters(1.25), &Meters(1.0) + Feet(1.0));
+    assert_eq!(Meters(1.125), Meters(1.0) + &Inches(1.0));
+    assert_eq!(Meters(1.25), &Feet(1.0) + &Meters(1.0));
+    assert_eq!(Meters(0.5), Feet(1.0) + Feet(1.0));
+    assert_eq!(Meters(0.375), Feet(1.0) + Inches(1.0));
+    assert_eq!(Meters(1.125), Inches(1.0) + Meters(1.0));
+    assert_eq!(Meters(0.375), Inches(1.0) + Feet(1.0));
+    assert_eq!(Meters(0.25), Inches(1.0) + Inches(1.0));
+}
+
+// These would conflict with the matrix if they weren't skipped
+impl_op_ex!(-|a: &Meters, b: &Meters| -> Meters { Meters(a.0 - b.0) });
+impl_op_ex!(-|a: &Feet, b: &Feet| -> Feet { Feet(a.0 - b.0) });
+
+impl_op_matrix!(- [Meters, Feet] x [Meters, Feet, Inches] skip [(Meters, Meters), (Feet, Feet)] #[inline] |a, b| -> Meters {
+    Meters(a.meters() - b.meters())
+});
+#[test]
+fn skip() {
+    assert_eq!(Meters(1.0), Meters(2.0) - Meters(1.0));
+    assert_eq!(Feet(1.0), Feet(2.0) - Feet(1.0));
+    assert_eq!(Meters(0.75), Meters(1.0) - Feet(1.0));
+    assert_eq!(Meters(0.875), &Meters(1.0) - &Inches(1.0));
+    assert_eq!(Meters(-0.75), Feet(1.0) - Meters(1.0));
+    assert_eq!(Meters(0.125), Feet(1.0) - Inches(1.0));
+}
+
+mod generics {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Wrapper<T>(T);
+
+    impl_op_matrix!(* [(Wrapper<u8>), (Wrapper<u16>)] x [u8, (Wrapper<u8>)] skip [((Wrapper<u16>), (Wrapper<u8>))] |a, _b| {
+        *a
+    });
+
+    #[test]
+    fn parenthesized_types() {
+        assert_eq!(Wrapper(1u8), Wrapper(1u8) * 2u8);
+        assert_eq!(Wrapper(1u8), Wrapper(1u8) * Wrapper(2u8));
+        assert_eq!(Wrapper(1u16), &Wrapper(1u16) * &2u8);
+    }
+}
@@ -0,0 +1,94 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use core::ops::Add;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Vec3 {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl Vec3 {
+    fn new(x: i32, y: i32, z: i32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Scale(i32);
+
+impl_op_ex!(+ mut |a: &Vec3, b: &Vec3| -> Vec3 { Vec3::new(a.x + b.x, a.y + b.y, a.z + b.z) });
+#[test]
+fn binary() {
+    let (mut a, mut b) = (Vec3::new(1, 2, 3), Vec3::new(4, 5, 6));
+    let sum = Vec3::new(5, 7, 9);
+    let (a, b) = (&mut a, &mut b);
+    assert_eq!(sum, &mut *a + &*b);
+    assert_eq!(sum, &mut *a + *b);
+    assert_eq!(sum, &mut *a + &mut *b);
+    assert_eq!(sum, &*a + &mut *b);
+    assert_eq!(sum, *a + &mut *b);
+}
+
+impl_op_ex!(- mut |a: &Vec3| -> Vec3 { Vec3::new(-a.x, -a.y, -a.z) });
+impl_op_ex!(*=, mut |a: &mut Vec3, b: &Scale| {
+    a.x *= b.0;
+    a.y *= b.0;
+    a.z *= b.0;
+});
+#[test]
+fn unary_and_assignment() {
+    let mut a = Vec3::new(1, 2, 3);
+    assert_eq!(Vec3::new(-1, -2, -3), -&mut a);
+
+    let mut s = Scale(2);
+    a *= &mut s;
+    assert_eq!(Vec3::new(2, 4, 6), a);
+}
+
+impl_op_ex_commutative!(* mut |a: &Vec3, b: Scale| -> Vec3 { Vec3::new(a.x * b.0, a.y * b.0, a.z * b.0) });
+#[test]
+fn commutative() {
+    let mut a = Vec3::new(1, 2, 3);
+    assert_eq!(Vec3::new(2, 4, 6), &mut a * Scale(2));
+    assert_eq!(Vec3::new(2, 4, 6), Scale(2) * &mut a);
+}
+
+// Generic code holding `&mut` operands no longer needs to reborrow them
+fn sum<T>(a: &mut T, b: &T) -> T
+where
+    for<'a, 'b> &'a mut T: Add<&'b T, Output = T>,
+{
+    a + b
+}
+#[test]
+fn generic_code() {
+    let mut a = Vec3::new(1, 2, 3);
+    assert_eq!(Vec3::new(2, 4, 6), sum(&mut a, &Vec3::new(1, 2, 3)));
+}
+
+mod blocks {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct W<T>(T);
+
+    impl_ops! {
+        <T: Copy> for W<T>;
+
+        impl_op_ex!(- mut |a: &Self, b: &Self| -> Self where T: core::ops::Sub<Output = T> { W(a.0 - b.0) });
+        impl_op_ex_commutative!(% mut |a: &Self, b: &Scale| -> i32 where T: Into<i32> { a.0.into() % b.0 });
+    }
+
+    #[test]
+    fn impl_ops() {
+        let (mut a, mut s) = (W(7), Scale(4));
+        assert_eq!(W(6), &mut a - W(1));
+        assert_eq!(W(0), &mut a - &mut W(7));
+        assert_eq!(3, &mut a % &mut s);
+        assert_eq!(3, &mut s % W(7));
+    }
+}
@@ -0,0 +1,138 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct V(i32, i32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Flags(u8);
+
+impl_op_ex!([+, -, *, /, %] |a: &V, b: &V| -> V { V(a.0 $op b.0, a.1 $op b.1) });
+#[test]
+fn binary() {
+    let (a, b) = (V(7, 8), V(2, 3));
+    assert_eq!(V(9, 11), a + b);
+    assert_eq!(V(5, 5), &a - b);
+    assert_eq!(V(14, 24), a * &b);
+    assert_eq!(V(3, 2), &a / &b);
+    assert_eq!(V(1, 2), a % b);
+}
+
+impl_op!([+=, -=, *=], |a: &mut V, b: i32| {
+    a.0 $op b;
+    a.1 $op b;
+});
+#[test]
+fn assignment() {
+    let mut a = V(1, 2);
+    a += 3;
+    assert_eq!(V(4, 5), a);
+    a -= 1;
+    assert_eq!(V(3, 4), a);
+    a *= 2;
+    assert_eq!(V(6, 8), a);
+}
+
+impl_op!([-, !] |a: V| -> V { V($op a.0, $op a.1) });
+#[test]
+fn unary() {
+    assert_eq!(V(-1, -2), -V(1, 2));
+    assert_eq!(V(!1, !2), !V(1, 2));
+}
+
+impl_op_ex_commutative!([&, |, ^] |a: &Flags, b: u8| -> Flags { Flags(a.0 $op b) });
+#[test]
+fn commutative() {
+    assert_eq!(Flags(0b0100), Flags(0b0110) & 0b1100);
+    assert_eq!(Flags(0b1110), 0b1100 | &Flags(0b0110));
+    assert_eq!(Flags(0b1010), Flags(0b0110) ^ 0b1100);
+}
+
+impl_op!([<<, >>] <T: Into<u32>> |a: Flags, b: T| -> Flags {
+    // Nothing besides the placeholder is replaced, even when it's named `op` as well
+    let op = b.into();
+    Flags(a.0 $op op)
+});
+#[test]
+fn generic_params() {
+    assert_eq!(Flags(0b0100), Flags(0b0001) << 2u8);
+    assert_eq!(Flags(0b0001), Flags(0b0100) >> 2u32);
+}
+
+mod op_identifier {
+    use super::*;
+
+    struct Holder {
+        op: i32,
+    }
+
+    mod x {
+        pub fn op(v: i32) -> i32 {
+            v * 2
+        }
+    }
+
+    // None of these are the placeholder, whatever token comes before `op`
+    impl_op!([+, -] |a: V, b: i32| -> V {
+        let op = Holder { op: x::op(b) };
+        V(a.0 $op op.op, a.1)
+    });
+    impl_op!([*, /] |a: V, b: i32| -> V {
+        assert_eq!("_ op", stringify!(_ op));
+        V(a.0 $op b, a.1)
+    });
+
+    #[test]
+    fn binary() {
+        assert_eq!(V(5, 2), V(1, 2) + 2);
+        assert_eq!(V(-3, 2), V(1, 2) - 2);
+        assert_eq!(V(2, 2), V(1, 2) * 2);
+        assert_eq!(V(3, 2), V(6, 2) / 2);
+    }
+}
+
+mod without_placeholder {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Unit;
+
+    impl_op_ex!([+, *] |_a: &Unit, _b: &Unit| -> Unit { Unit });
+
+    #[test]
+    fn binary() {
+        assert_eq!(Unit, Unit + Unit);
+        assert_eq!(Unit, &Unit * &Unit);
+    }
+}
+
+mod blocks {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct W<T>(T);
+
+    impl_ops! {
+        <T: Copy> for W<T>;
+
+        impl_op_ex!([+, -] |a: &Self, b: &Self| -> Self where T: ::core::ops::Add<Output = T> + ::core::ops::Sub<Output = T> {
+            W(a.0 $op b.0)
+        });
+        impl_op!([+=, -=], |a: &mut Self, b: T| where T: ::core::ops::AddAssign + ::core::ops::SubAssign {
+            a.0 $op b;
+        });
+    }
+
+    #[test]
+    fn impl_ops() {
+        assert_eq!(W(5), W(2) + &W(3));
+        assert_eq!(W(-1), &W(2) - W(3));
+
+        let mut w = W(1.5);
+        w += 1.0;
+        w -= 0.5;
+        assert_eq!(W(2.0), w);
+    }
+}
@@ -0,0 +1,112 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Buf(Vec<i32>);
+
+impl_op_ex!(+= |a: &mut Buf, b: &Buf| {
+    for (x, y) in a.0.iter_mut().zip(&b.0) {
+        *x += y;
+    }
+});
+impl_op_ex!(+ reuse |a: &Buf, b: &Buf| -> Buf {
+    Buf(a.0.iter().zip(&b.0).map(|(x, y)| x + y).collect())
+});
+#[test]
+fn borrowed_rhs() {
+    let (a, b) = (Buf(vec![1, 2]), Buf(vec![3, 4]));
+    assert_eq!(Buf(vec![4, 6]), &a + &b);
+    assert_eq!(Buf(vec![4, 6]), &a + b.clone());
+
+    let ptr = a.0.as_ptr();
+    let c = a + &b;
+    assert_eq!(Buf(vec![4, 6]), c);
+    assert_eq!(ptr, c.0.as_ptr());
+    let d = c + b;
+    assert_eq!(Buf(vec![7, 10]), d);
+    assert_eq!(ptr, d.0.as_ptr());
+}
+
+impl_op_ex!(*= |a: &mut Buf, b: i32| {
+    for x in &mut a.0 {
+        *x *= b;
+    }
+});
+impl_op_ex!(*, reuse #[inline] |a: &Buf, b: i32| -> Self {
+    Buf(a.0.iter().map(|x| x * b).collect())
+});
+#[test]
+fn owned_rhs() {
+    let a = Buf(vec![1, 2]);
+    assert_eq!(Buf(vec![2, 4]), &a * 2);
+
+    let ptr = a.0.as_ptr();
+    let b = a * 3;
+    assert_eq!(Buf(vec![3, 6]), b);
+    assert_eq!(ptr, b.0.as_ptr());
+}
+
+impl_op_ex!(^= |a: &mut Buf, b: &Buf| {
+    for (x, y) in a.0.iter_mut().zip(&b.0) {
+        *x ^= y;
+    }
+});
+impl_op_ex!(^ reuse mut |a: &Buf, b: &Buf| -> Buf {
+    Buf(a.0.iter().zip(&b.0).map(|(x, y)| x ^ y).collect())
+});
+#[test]
+fn mut_refs() {
+    let (mut a, mut b) = (Buf(vec![3, 4]), Buf(vec![1, 2]));
+    assert_eq!(Buf(vec![2, 6]), &mut a ^ &mut b);
+
+    let ptr = a.0.as_ptr();
+    let c = a ^ &mut b;
+    assert_eq!(Buf(vec![2, 6]), c);
+    assert_eq!(ptr, c.0.as_ptr());
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct W<T>(T);
+
+impl_op_ex!([-=, /=] <T: Copy> |a: &mut W<T>, b: &W<T>| where T: core::ops::SubAssign + core::ops::DivAssign {
+    a.0 $op b.0;
+});
+impl_op_ex!([-, /] reuse <T: Copy> |a: &W<T>, b: &W<T>| -> W<T> where T: core::ops::Sub<Output = T> + core::ops::Div<Output = T> + core::ops::SubAssign + core::ops::DivAssign {
+    W(a.0 $op b.0)
+});
+#[test]
+fn generics() {
+    assert_eq!(W(2), W(3) - W(1));
+    assert_eq!(W(2), &W(3) - &W(1));
+    assert_eq!(W(1.5), W(3.0) / &W(2.0));
+}
+
+mod blocks {
+    use super::*;
+
+    impl_ops! {
+        for Buf;
+
+        impl_op_ex!(-= |a: &mut Self, b: &Self| {
+            for (x, y) in a.0.iter_mut().zip(&b.0) {
+                *x -= y;
+            }
+        });
+        impl_op_ex!(- reuse |a: &Self, b: &Self| -> Self {
+            Buf(a.0.iter().zip(&b.0).map(|(x, y)| x - y).collect())
+        });
+    }
+
+    #[test]
+    fn impl_ops() {
+        let a = Buf(vec![3, 4]);
+        assert_eq!(Buf(vec![0, 0]), &a - &a);
+
+        let ptr = a.0.as_ptr();
+        let b = a - Buf(vec![1, 1]);
+        assert_eq!(Buf(vec![2, 3]), b);
+        assert_eq!(ptr, b.0.as_ptr());
+    }
+}
@@ -0,0 +1,77 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![cfg(feature = "alloc")]
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Node(i32);
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Leaf(i32);
+
+impl_op_ex!(+ ptr |a: &Node, b: &Leaf| -> Node { Node(a.0 + b.0) });
+#[test]
+fn binary() {
+    let sum = Node(3);
+    assert_eq!(sum, Box::new(Node(1)) + Box::new(Leaf(2)));
+    assert_eq!(sum, Box::new(Node(1)) + &Leaf(2));
+    assert_eq!(sum, Node(1) + Arc::new(Leaf(2)));
+    assert_eq!(sum, Node(1) + Rc::new(Leaf(2)));
+    assert_eq!(sum, &Node(1) + Arc::new(Leaf(2)));
+    assert_eq!(sum, Box::new(Node(1)) + Rc::new(Leaf(2)));
+    assert_eq!(sum, &Node(1) + &Leaf(2));
+}
+
+impl_op_ex!(* ptr |a: &Node, b: i32| -> Node { Node(a.0 * b) });
+impl_op_ex!(% ptr |a: &Node, b: &i32| -> Node { Node(a.0 % b) });
+impl_op_ex!(^ ptr |a: &Node, b: &String| -> usize { a.0 as usize ^ b.len() });
+impl_op_ex!(/ ptr |a: i32, b: &Node| -> Node { Node(a / b.0) });
+#[test]
+fn owned_operand() {
+    assert_eq!(Node(6), Box::new(Node(3)) * 2);
+    assert_eq!(Node(6), Node(3) * 2);
+    assert_eq!(Node(2), 6 / Box::new(Node(3)));
+}
+
+#[test]
+fn foreign_rhs() {
+    assert_eq!(Node(1), Box::new(Node(7)) % Rc::new(3));
+    assert_eq!(Node(1), &Node(7) % Arc::new(3));
+    assert_eq!(Node(1), Node(7) % Box::new(3));
+    assert_eq!(6, Box::new(Node(3)) ^ String::from("hello"));
+    assert_eq!(6, Node(3) ^ Rc::new(String::from("hello")));
+}
+
+impl_op_ex!(- ptr |a: &Node| -> Node { Node(-a.0) });
+impl_op_ex!(+= ptr |a: &mut Node, b: &Leaf| { a.0 += b.0; });
+#[test]
+fn unary_and_assignment() {
+    assert_eq!(Node(-1), -Box::new(Node(1)));
+
+    let mut a = Node(1);
+    a += Box::new(Leaf(2));
+    a += Rc::new(Leaf(3));
+    a += Arc::new(Leaf(4));
+    assert_eq!(Node(10), a);
+}
+
+mod blocks {
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct W<T>(T);
+
+    impl_ops! {
+        <T: Copy> for W<T>;
+
+        impl_op_ex!(- ptr |a: &Self, b: &Self| -> Self where T: core::ops::Sub<Output = T> { W(a.0 - b.0) });
+    }
+
+    #[test]
+    fn impl_ops() {
+        assert_eq!(W(1), Box::new(W(3)) - Rc::new(W(2)));
+        assert_eq!(W(1.5), W(3.0) - Arc::new(W(1.5)));
+    }
+}
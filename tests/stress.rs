@@ -0,0 +1,116 @@
+// Some of the generic params and where clauses below are over a hundred tokens long, which used to
+// take one macro recursion per token. Keep the limit well below that so regressions show up here.
+#![recursion_limit = "64"]
+// Operands are deliberately borrowed to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+use core::fmt::{Debug, Display};
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Grid<T> {
+    cells: [T; 2],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Pair<A, B, C, D>(A, B, C, D);
+
+impl_op_ex!(+ <T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + Rem<Output = T> + Neg<Output = T> + Copy + Clone + Default + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static>
+    |a: &Grid<T>, b: &Grid<T>| -> Grid<T>
+{
+    let mut cells = [T::default(); 2];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        *cell = a.cells[i] + b.cells[i];
+    }
+    Grid { cells }
+});
+
+impl_op!(-= <T: Sub<Output = T> + Mul<Output = T> + Copy + Clone + Default + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static>
+    |a: &mut Grid<T>, b: T|
+{
+    for cell in a.cells.iter_mut() {
+        *cell = *cell - b;
+    }
+});
+
+impl_op_ex!(- <T: Neg<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy + Clone + Default + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static>
+    |a: &Grid<T>| -> Grid<T>
+{
+    let mut cells = a.cells;
+    for cell in cells.iter_mut() {
+        *cell = -*cell;
+    }
+    Grid { cells }
+});
+
+impl_op_ex_commutative!(* <'a, 'b: 'a, A: Add<Output = A> + AddAssign<A> + Copy + Debug + PartialEq + 'a, B: Mul<Output = B> + Copy + Debug + PartialEq + 'b, C: Clone + Debug + Default + PartialEq + Send + Sync + 'static, D: Into<u64> + From<u8> + Copy + Debug + PartialEq + Send + Sync + 'static>
+    |a: &Pair<A, B, C, D>, b: &u64| -> u64
+    where A: Send + Sync, B: Send + Sync
+{
+    a.3.into() * *b
+});
+
+impl_ops! {
+    <T> for Grid<T> where T: Div<Output = T> + Rem<Output = T> + Copy + Clone + Default + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static;
+
+    impl_op_ex!(/ |a: &Self, b: &Self| -> Self
+        where Self: Copy + Clone + Debug + PartialEq + Send + Sync + 'static, T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Neg<Output = T> + PartialOrd + Debug + Display + Send + Sync + 'static
+    {
+        let mut cells = a.cells;
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = *cell / b.cells[i];
+        }
+        Grid { cells }
+    });
+    impl_op_ex!(% <U: Into<T> + Mul<Output = U> + Copy + Clone + Default + PartialEq + PartialOrd + Debug + Display + Send + Sync + 'static> |a: &Self, b: &Grid<U>| -> Self {
+        let mut cells = a.cells;
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = *cell % b.cells[i].into();
+        }
+        Grid { cells }
+    });
+}
+
+#[test]
+fn binary() {
+    let a = Grid { cells: [1, 2] };
+    let b = Grid { cells: [4, 5] };
+    assert_eq!(Grid { cells: [5, 7] }, a + b);
+    assert_eq!(Grid { cells: [5, 7] }, &a + b);
+    assert_eq!(Grid { cells: [5, 7] }, a + &b);
+    assert_eq!(Grid { cells: [5, 7] }, &a + &b);
+}
+
+#[test]
+fn impl_ops() {
+    let a = Grid { cells: [8, 9] };
+    let b = Grid { cells: [2, 4] };
+    assert_eq!(Grid { cells: [4, 2] }, a / b);
+    assert_eq!(Grid { cells: [4, 2] }, &a / &b);
+    assert_eq!(Grid { cells: [0, 1] }, a % Grid { cells: [2u8, 4u8] });
+    assert_eq!(Grid { cells: [0, 1] }, &a % &Grid { cells: [2u8, 4u8] });
+}
+
+#[test]
+fn assignment() {
+    let mut a = Grid { cells: [1.5, 2.5] };
+    a -= 0.5;
+    assert_eq!(Grid { cells: [1.0, 2.0] }, a);
+}
+
+#[test]
+fn unary() {
+    let a = Grid { cells: [1, -2] };
+    assert_eq!(Grid { cells: [-1, 2] }, -a);
+    assert_eq!(Grid { cells: [-1, 2] }, -&a);
+}
+
+#[test]
+fn commutative() {
+    let pair = Pair(1, 2.0, String::new(), 3u8);
+    assert_eq!(6, &pair * &2);
+    assert_eq!(6, pair.clone() * 2);
+    assert_eq!(6, &2 * &pair);
+    assert_eq!(6, 2 * pair);
+}
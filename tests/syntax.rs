@@ -1,9 +1,25 @@
+// Most of these tests invoke the macros inside of the test functions themselves, some
+// deliberately borrow operands to exercise the `impl_op_ex!` variants, and the bodies don't always
+// use the operator they implement
+#![allow(
+    non_local_definitions,
+    clippy::op_ref,
+    clippy::suspicious_arithmetic_impl
+)]
+
 use auto_ops::*;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 struct Foo(i32);
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 struct Bar<A, B>(A, B);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Baz(i32);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
 
 #[test]
 fn regular() {
@@ -22,6 +38,28 @@ fn attributes() {
 
     assert_eq!(Foo(1) & Foo(2), Foo(1 & 2));
 }
+
+#[test]
+fn impl_attributes() {
+    // These would conflict with the impls below if the `cfg` didn't reach every impl block
+    impl_op_ex!(% impl #[cfg(any())] |a: &Foo, b: &Baz| -> Foo { Foo(a.0 % b.0) });
+    impl_op!(%= impl #[cfg(any())] |a: &mut Foo, b: Baz| { a.0 %= b.0; });
+
+    impl_op_ex!(% #[inline] impl #[cfg(all())] #[allow(clippy::suspicious_arithmetic_impl)] |a: &Foo, b: &Baz| -> Foo {
+        Foo(a.0 % b.0)
+    });
+    impl_op!(%=, #[inline] impl #[cfg(all())] |a: &mut Foo, b: Baz| { a.0 %= b.0; });
+
+    assert_eq!(Foo(7) % Baz(4), Foo(3));
+    assert_eq!(&Foo(7) % &Baz(4), Foo(3));
+    let mut foo = Foo(7);
+    foo %= Baz(4);
+    assert_eq!(foo, Foo(3));
+    let mut foo = &mut Foo(7);
+    foo %= Baz(4);
+    assert_eq!(*foo, Foo(3));
+}
+
 #[test]
 fn underscore() {
     impl_op!(+|_: Foo, _: Foo| -> Foo {
@@ -53,6 +91,139 @@ fn parens() {
     assert_eq!(Foo(4) / Foo(2), Foo(2));
 }
 
+#[test]
+fn tuple_struct_pattern() {
+    impl_op!(^|Foo(a): Foo, Foo(b): Foo| -> Foo { Foo(a ^ b) });
+
+    assert_eq!(Foo(6) ^ Foo(3), Foo(5));
+}
+
+#[test]
+fn struct_pattern() {
+    impl_op_ex!(%|Point { x, y }: &Point, Foo(m): &Foo| -> Point {
+        Point { x: x % m, y: y % m }
+    });
+
+    assert_eq!(Point { x: 7, y: 5 } % Foo(3), Point { x: 1, y: 2 });
+    assert_eq!(&Point { x: 7, y: 5 } % Foo(3), Point { x: 1, y: 2 });
+    assert_eq!(Point { x: 7, y: 5 } % &Foo(3), Point { x: 1, y: 2 });
+}
+
+#[test]
+fn nested_pattern() {
+    impl_op!(^|Bar(Foo(a), Point { x, .. }): Bar<Foo, Point>, (b, _): (i32, i32)| -> i32 {
+        a ^ x ^ b
+    });
+
+    assert_eq!(Bar(Foo(1), Point { x: 2, y: 0 }) ^ (4, 8), 1 ^ 2 ^ 4);
+}
+
+#[test]
+fn ref_pattern() {
+    impl_op_ex!(*= |Point { ref mut x, ref mut y }: &mut Point, Foo(ref m): &Foo| {
+        *x *= *m;
+        *y *= *m;
+    });
+    impl_op_ex!(-|Point { ref x, ref y }: &Point| -> Point { Point { x: -*x, y: -*y } });
+
+    let mut point = Point { x: 1, y: 2 };
+    point *= Foo(3);
+    assert_eq!(point, Point { x: 3, y: 6 });
+    point *= &Foo(2);
+    assert_eq!(point, Point { x: 6, y: 12 });
+
+    assert_eq!(-&point, Point { x: -6, y: -12 });
+    assert_eq!(-point, Point { x: -6, y: -12 });
+}
+
+#[test]
+fn trait_names() {
+    impl_op_ex!(Shl |a: &Baz, b: &Baz| -> Baz { Baz(a.0 << b.0) });
+    impl_op!(ShrAssign |a: &mut Baz, b: i32| { a.0 >>= b; });
+    impl_op!(Not |a: Baz| -> Baz { Baz(!a.0) });
+
+    assert_eq!(Baz(1) << Baz(2), Baz(4));
+    assert_eq!(&Baz(1) << Baz(3), Baz(8));
+
+    let mut baz = Baz(8);
+    baz >>= 2;
+    assert_eq!(baz, Baz(2));
+
+    assert_eq!(!Baz(0), Baz(-1));
+}
+
+#[test]
+fn trait_names_forwarded() {
+    macro_rules! impl_baz_op {
+        ($ops_trait:ident, $f:expr) => {
+            impl_op!($ops_trait |a: Baz, b: i32| -> Baz { Baz($f(a.0, b)) });
+        };
+    }
+    impl_baz_op!(Add, |a, b| a + b);
+    impl_baz_op!(Mul, |a, b| a * b);
+
+    assert_eq!(Baz(2) + 3, Baz(5));
+    assert_eq!(Baz(2) * 3, Baz(6));
+}
+
+#[test]
+fn ownership_keywords() {
+    impl_op_ex!(+= |a: mutref Point, b: ref Baz| {
+        a.x += b.0;
+        a.y += b.0;
+    });
+    impl_op_ex!(- <'a> |a: ref 'a Point, _b: own Baz| -> &'a i32 { &a.x });
+
+    let mut point = Point { x: 1, y: 2 };
+    point += Baz(1);
+    point += &Baz(1);
+    assert_eq!(point, Point { x: 3, y: 4 });
+    assert_eq!(&point - Baz(0), &3);
+}
+
+#[test]
+fn ownership_keywords_forwarded() {
+    // `$lhs` is opaque once captured, so `&$lhs` couldn't be split back out by `impl_op!`
+    macro_rules! impl_borrowed_op {
+        ($op:tt, $lhs:ty, $rhs:ty) => {
+            impl_op!($op |a: ref $lhs, b: own $rhs| -> i32 { a.0 + b.0 });
+        };
+    }
+    impl_borrowed_op!(|, Baz, Foo);
+    impl_borrowed_op!(|, &Baz, Foo);
+
+    assert_eq!(&Baz(1) | Foo(2), 3);
+    assert_eq!(&&Baz(1) | Foo(2), 3);
+}
+
+#[test]
+fn self_output() {
+    impl_op_ex!(-|a: &Baz, b: &Baz| -> Self { Baz(a.0 - b.0) });
+    impl_op_ex!(- <A: Copy, B: Copy> |a: &Bar<A, B>| -> Self where A: ::core::ops::Neg<Output = A> {
+        Bar(-a.0, a.1)
+    });
+    impl_op_ex!(/ |a: &Baz, b: &Baz| -> Option<Self> { a.0.checked_div(b.0).map(Baz) });
+
+    assert_eq!(Baz(3) - Baz(1), Baz(2));
+    assert_eq!(&Baz(3) - &Baz(1), Baz(2));
+    assert_eq!(-Bar(1.5, 'a'), Bar(-1.5, 'a'));
+    assert_eq!(-&Bar(1.5, 'a'), Bar(-1.5, 'a'));
+    assert_eq!(Baz(6) / Baz(2), Some(Baz(3)));
+    assert_eq!(&Baz(6) / &Baz(0), None);
+}
+
+#[test]
+fn omitted_output() {
+    impl_op_ex!(*|a: &Bar<i32, u8>, b: i32| { Bar(a.0 * b, a.1) });
+    impl_op!(! <A, B> |a: &Bar<A, B>| where A: Copy + ::core::ops::Not<Output = A>, B: Copy {
+        Bar(!a.0, a.1)
+    });
+
+    assert_eq!(Bar(2, 1u8) * 3, Bar(6, 1u8));
+    assert_eq!(&Bar(2, 1u8) * 3, Bar(6, 1u8));
+    assert_eq!(!&Bar(true, 'a'), Bar(false, 'a'));
+}
+
 #[test]
 fn generic_params() {
     impl_op!(/<A: Copy + 'static, B: ::core::ops::Add<A>>|a: Bar<A, B>, b: Bar<A, B>| -> B::Output {
@@ -61,3 +232,25 @@ fn generic_params() {
 
     assert_eq!(Bar(3, 0) / Bar(0, 2), 5);
 }
+
+#[test]
+fn where_clause() {
+    impl_op_ex!(+ <A, B> |a: &Bar<A, B>, b: &Bar<A, B>| -> Bar<A, B>
+        where for<'a> &'a A: ::core::ops::Add<&'a A, Output = A>, B: Copy
+    {
+        Bar(&a.0 + &b.0, a.1)
+    });
+    impl_op!(-= <A, B> |a: &mut Bar<A, B>, b: A| where A: ::core::ops::SubAssign<A> {
+        a.0 -= b;
+    });
+    impl_op!(! <A, B> |a: Bar<A, B>| -> Bar<B, A> where A: Copy, B: Copy { Bar(a.1, a.0) });
+
+    assert_eq!(Bar(1, 'a') + Bar(2, 'b'), Bar(3, 'a'));
+    assert_eq!(&Bar(1, 'a') + Bar(2, 'b'), Bar(3, 'a'));
+
+    let mut bar = Bar(5, 'a');
+    bar -= 2;
+    assert_eq!(bar, Bar(3, 'a'));
+
+    assert_eq!(!bar, Bar('a', 3));
+}
@@ -0,0 +1,111 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants
+#![allow(clippy::op_ref)]
+
+use auto_ops::*;
+
+mod ops {
+    pub trait Dot<Rhs = Self> {
+        type Output;
+        fn dot(self, rhs: Rhs) -> Self::Output;
+    }
+
+    pub trait Cross<Rhs = Self> {
+        type Output;
+        fn cross(self, rhs: Rhs) -> Self::Output;
+    }
+
+    pub trait Pow<Rhs> {
+        type Result;
+        fn pow(self, rhs: Rhs) -> Self::Result;
+    }
+}
+
+use ops::{Cross, Dot, Pow};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Vector {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Scale(i32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Wrapper<T>(T);
+
+mod impl_op {
+    use super::*;
+
+    impl_op!(trait ops::Cross::cross |a: Vector, b: Vector| -> i32 { a.x * b.y - a.y * b.x });
+    #[test]
+    fn owned() {
+        assert_eq!(-2, Vector { x: 1, y: 2 }.cross(Vector { x: 3, y: 4 }));
+    }
+
+    impl_op!(trait crate::ops::Cross::cross |a: &Vector, b: &Vector| -> i32 { a.x * b.y - a.y * b.x });
+    #[test]
+    fn borrowed() {
+        assert_eq!(-2, (&Vector { x: 1, y: 2 }).cross(&Vector { x: 3, y: 4 }));
+    }
+}
+
+mod impl_op_ex {
+    use super::*;
+
+    impl_op_ex!(trait Dot::dot |a: &Vector, b: &Vector| -> i32 { a.x * b.x + a.y * b.y });
+    #[test]
+    fn all_variants() {
+        let (a, b) = (Vector { x: 1, y: 2 }, Vector { x: 3, y: 4 });
+        assert_eq!(11, a.dot(b));
+        assert_eq!(11, a.dot(&b));
+        assert_eq!(11, (&a).dot(b));
+        assert_eq!(11, (&a).dot(&b));
+    }
+
+    impl_op_ex!(trait ::core::ops::Mul::mul |a: &Vector, b: Scale| -> Vector {
+        Vector { x: a.x * b.0, y: a.y * b.0 }
+    });
+    #[test]
+    fn core_trait() {
+        assert_eq!(Vector { x: 2, y: 4 }, Vector { x: 1, y: 2 } * Scale(2));
+        assert_eq!(Vector { x: 2, y: 4 }, &Vector { x: 1, y: 2 } * Scale(2));
+    }
+}
+
+mod commutative {
+    use super::*;
+
+    impl_op_ex_commutative!(trait Dot::dot |a: &Vector, b: &Scale| -> Vector {
+        Vector { x: a.x * b.0, y: a.y * b.0 }
+    });
+    #[test]
+    fn impl_op_ex_commutative() {
+        let (v, s) = (Vector { x: 1, y: 2 }, Scale(3));
+        assert_eq!(Vector { x: 3, y: 6 }, v.dot(s));
+        assert_eq!(Vector { x: 3, y: 6 }, (&v).dot(&s));
+        assert_eq!(Vector { x: 3, y: 6 }, s.dot(v));
+        assert_eq!(Vector { x: 3, y: 6 }, (&s).dot(&v));
+    }
+}
+
+mod associated_type {
+    use super::*;
+
+    impl_op_ex!(trait Pow::pow type Result, #[inline] |a: &Scale, b: u32| -> Scale { Scale(a.0.pow(b)) });
+    #[test]
+    fn renamed_output() {
+        assert_eq!(Scale(8), Scale(2).pow(3));
+        assert_eq!(Scale(8), (&Scale(2)).pow(3));
+    }
+
+    impl_op!(trait Pow::pow type Result <T: Copy + ::core::ops::Mul<Output = T>> |a: Wrapper<T>, b: Wrapper<T>| -> T
+        where T: ::core::fmt::Debug
+    {
+        a.0 * b.0
+    });
+    #[test]
+    fn generics() {
+        assert_eq!(6, Wrapper(2).pow(Wrapper(3)));
+    }
+}
@@ -0,0 +1,131 @@
+// Some of these tests deliberately borrow operands to exercise the `impl_op_ex!` variants, and
+// the bodies shared by several types convert to types some of them already are
+#![allow(clippy::op_ref, clippy::unnecessary_cast, clippy::useless_conversion)]
+
+use auto_ops::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Vec3 {
+    fn new(x: f64, y: f64, z: f64) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Meters(f64);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Feet(f64);
+
+impl_op_ex_commutative!(* |a: &Vec3, b: {f32, f64, i32, u8}| -> Vec3 {
+    let b = b as f64;
+    Vec3::new(a.x * b, a.y * b, a.z * b)
+});
+#[test]
+fn commutative() {
+    let a = Vec3::new(1.0, 2.0, 3.0);
+    let doubled = Vec3::new(2.0, 4.0, 6.0);
+    assert_eq!(doubled, a * 2f32);
+    assert_eq!(doubled, &a * 2f64);
+    assert_eq!(doubled, 2i32 * a);
+    assert_eq!(doubled, 2u8 * &a);
+}
+
+impl_op!(+ |a: {Meters, Feet}, b: {Meters, Feet}| -> f64 { a.0 + b.0 });
+#[test]
+fn both_operands() {
+    assert_eq!(3.0, Meters(1.0) + Meters(2.0));
+    assert_eq!(3.0, Meters(1.0) + Feet(2.0));
+    assert_eq!(3.0, Feet(1.0) + Meters(2.0));
+    assert_eq!(3.0, Feet(1.0) + Feet(2.0));
+}
+
+impl_op_ex!(- |a: &{Meters, Feet}, b: ref {f32, f64}| -> f64 { a.0 - *b as f64 });
+impl_op!(/ <'a> |a: &'a Vec3, _b: ref 'a {u8, u16}| -> &'a f64 { &a.x });
+#[test]
+fn borrowed() {
+    assert_eq!(1.0, Meters(3.0) - 2f32);
+    assert_eq!(1.0, &Feet(3.0) - &2f64);
+    assert_eq!(&1.0, &Vec3::new(1.0, 2.0, 3.0) / &2u16);
+}
+
+// A borrow can be written on each type instead of in front of the list
+impl_op!(% |a: Vec3, b: {&f32, &'static f64}| -> f64 { a.x % *b as f64 });
+impl_op!(^, #[allow(clippy::suspicious_arithmetic_impl)] |a: {&Meters, Feet}, b: {Meters, &Feet}| -> f64 {
+    a.0 + b.0
+});
+impl_op!(%= |a: {&mut Meters, &mut Feet}, b: f64| { a.0 %= b; });
+#[test]
+fn borrowed_types() {
+    assert_eq!(1.0, Vec3::new(3.0, 0.0, 0.0) % &2f32);
+    assert_eq!(1.0, Vec3::new(3.0, 0.0, 0.0) % &2f64);
+    assert_eq!(3.0, &Meters(1.0) ^ Meters(2.0));
+    assert_eq!(3.0, &Meters(1.0) ^ &Feet(2.0));
+    assert_eq!(3.0, Feet(1.0) ^ Meters(2.0));
+    assert_eq!(3.0, Feet(1.0) ^ &Feet(2.0));
+
+    let mut meters = Meters(3.0);
+    meters %= 2.0;
+    assert_eq!(Meters(1.0), meters);
+    let mut feet = Feet(3.0);
+    feet %= 2.0;
+    assert_eq!(Feet(1.0), feet);
+}
+
+impl_op!(*= |a: &mut Vec3, b: {f32, f64}| {
+    let b = b as f64;
+    a.x *= b;
+    a.y *= b;
+    a.z *= b;
+});
+#[test]
+fn assignment() {
+    let mut a = Vec3::new(1.0, 2.0, 3.0);
+    a *= 2f32;
+    a *= 0.5f64;
+    assert_eq!(Vec3::new(1.0, 2.0, 3.0), a);
+}
+
+impl_op_ex!(- fn(a: &{Meters, Feet}) -> Self {
+    let mut negated = *a;
+    negated.0 = -a.0;
+    negated
+});
+#[test]
+fn unary() {
+    assert_eq!(Meters(-1.0), -Meters(1.0));
+    assert_eq!(Feet(-1.0), -&Feet(1.0));
+}
+
+mod blocks {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Scale(i64);
+
+    impl_ops! {
+        for Scale;
+
+        impl_op_ex_commutative!(* |a: &Self, b: {i32, i64}| -> Self { Scale(a.0 * b as i64) });
+        impl_op!(% |a: Self, b: {Self, i64}| -> Self { Scale(a.0 % Scale::from(b).0) });
+    }
+
+    impl From<i64> for Scale {
+        fn from(n: i64) -> Scale {
+            Scale(n)
+        }
+    }
+
+    #[test]
+    fn impl_ops() {
+        assert_eq!(Scale(6), Scale(3) * 2i32);
+        assert_eq!(Scale(6), 2i64 * &Scale(3));
+        assert_eq!(Scale(1), Scale(7) % Scale(3));
+        assert_eq!(Scale(1), Scale(7) % 3i64);
+    }
+}
@@ -0,0 +1,15 @@
+// `#[track_caller]` is newer than the minimum Rust version of `auto_ops`
+use auto_ops::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Foo(i32);
+
+impl_op_ex!(-, #[track_caller] |_a: &Foo| -> u32 {
+    core::panic::Location::caller().line()
+});
+
+#[test]
+fn track_caller() {
+    assert_eq!(-&Foo(1), line!());
+    assert_eq!(-Foo(1), line!());
+}
@@ -0,0 +1,7 @@
+// The snapshots depend on the compiler version, so this runs on stable only. Run with
+// `TRYBUILD=overwrite` to update them after changing a message.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
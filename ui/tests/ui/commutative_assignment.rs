@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_ex_commutative!(+= |a: &mut DonkeyKong, b: i32| { a.bananas += b; });
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_commutative!(- |a: DonkeyKong| -> DonkeyKong { a });
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op!(* |a: &DonkeyKong| -> &mut i32 { &mut a.bananas });
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_matrix!(+ [DonkeyKong] x [i32] |a: DonkeyKong, b: i32| -> i32 { a.bananas + b });
+
+fn main() {}
@@ -0,0 +1,11 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_ex!(- fold(DonkeyKong { bananas: 0 }) |a: &DonkeyKong, b: &DonkeyKong| -> DonkeyKong {
+    DonkeyKong { bananas: a.bananas - b.bananas }
+});
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_ex!(- reuse |a: &DonkeyKong| -> DonkeyKong { DonkeyKong { bananas: -a.bananas } });
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op!(+ |a: DonkeyKong, b: i32, c: i32| -> i32 { a.bananas + b + c });
+
+fn main() {}
@@ -0,0 +1,13 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+trait Double {
+    fn double(self, other: i32) -> i32;
+}
+
+impl_op!(trait Double::double |a: DonkeyKong| -> i32 { a.bananas });
+
+fn main() {}
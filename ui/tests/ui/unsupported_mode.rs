@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op_ex!(== mut |a: &DonkeyKong, b: &i32| -> bool { a.bananas == *b });
+
+fn main() {}
@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op!(&& |a: DonkeyKong, b: bool| -> bool { a.bananas > 0 && b });
+
+fn main() {}
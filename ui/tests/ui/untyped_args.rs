@@ -0,0 +1,9 @@
+use auto_ops::*;
+
+struct DonkeyKong {
+    bananas: i32,
+}
+
+impl_op!(+ |a, b| -> i32 { a.bananas + b });
+
+fn main() {}